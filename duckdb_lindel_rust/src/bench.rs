@@ -0,0 +1,90 @@
+// duckdb_lindel_rust
+// Copyright 2024 Rusty Conover <rusty@conover.me>
+// Licensed under the MIT License
+
+// Timings that justify the specialized code paths against the generic ones
+// they replace.
+//
+// The crate is built as a staticlib only and has no dependencies beyond
+// lindel, so rather than a criterion bench target these are ignored tests
+// that print their timings, run them with:
+//
+//   cargo test --release -- --ignored --nocapture bench::
+
+use super::*;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+// Random pairs of u32 coordinates, generated ahead of the timed loops.
+fn points(count: usize) -> Vec<[u32; 2]> {
+    let mut state: u64 = 0x853C_49E6_748F_EA9B;
+    (0..count)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            [state as u32, (state >> 32) as u32]
+        })
+        .collect()
+}
+
+// Time decoding every key with `f`, keeping the fastest of a few runs.
+fn time_decode<K: Copy, R>(name: &str, keys: &[K], f: impl Fn(K) -> R) -> Duration {
+    let elapsed = (0..3)
+        .map(|_| {
+            let start = Instant::now();
+            for &key in keys {
+                black_box(f(black_box(key)));
+            }
+            start.elapsed()
+        })
+        .min()
+        .unwrap();
+    println!("{name}: {elapsed:?} for {} keys", keys.len());
+    elapsed
+}
+
+#[test]
+#[ignore]
+fn morton_table_decode() {
+    const KEYS: usize = 1_000_000;
+    let points = points(KEYS);
+    let keys_2d_u32: Vec<u64> = points.iter().map(|&p| lindel::morton_encode(p)).collect();
+    let keys_3d_u16: Vec<u64> = points
+        .iter()
+        .map(|p| lindel::morton_encode([p[0] as u16, (p[0] >> 16) as u16, p[1] as u16]))
+        .collect();
+    let keys_3d_u32: Vec<u128> = points
+        .iter()
+        .map(|p| lindel::morton_encode([p[0], p[1], p[0] ^ p[1]]))
+        .collect();
+    let keys_2d_u64: Vec<u128> = points
+        .iter()
+        .map(|p| lindel::morton_encode([(p[0] as u64) << 32 | p[1] as u64, p[0] as u64]))
+        .collect();
+
+    time_decode("2D u32 table", &keys_2d_u32, |key| {
+        morton::decode::<2>(key as u128, 32)
+    });
+    time_decode("2D u32 lindel", &keys_2d_u32, |key| {
+        lindel::morton_decode::<u32, 2>(key)
+    });
+    time_decode("3D u16 table", &keys_3d_u16, |key| {
+        morton::decode::<3>(key as u128, 16)
+    });
+    time_decode("3D u16 lindel", &keys_3d_u16, |key| {
+        lindel::morton_decode::<u16, 3>(key)
+    });
+    time_decode("3D u32 table", &keys_3d_u32, |key| {
+        morton::decode::<3>(key, 32)
+    });
+    time_decode("3D u32 lindel", &keys_3d_u32, |key| {
+        lindel::morton_decode::<u32, 3>(key)
+    });
+    time_decode("2D u64 table", &keys_2d_u64, |key| {
+        morton::decode::<2>(key, 64)
+    });
+    time_decode("2D u64 lindel", &keys_2d_u64, |key| {
+        lindel::morton_decode::<u64, 2>(key)
+    });
+}
@@ -0,0 +1,84 @@
+// duckdb_lindel_rust
+// Copyright 2024 Rusty Conover <rusty@conover.me>
+// Licensed under the MIT License
+
+// Linear transforms of the bits of a value over GF(2).
+//
+// A matrix of n rows transforms an n bit value, bit `i` of the result is the
+// parity of the bits of the value selected by row `i`, so the identity matrix
+// has row `i` equal to `1 << i`.
+
+// Transform `value` by `matrix`.
+pub(crate) fn apply(matrix: &[u64], value: u64) -> u64 {
+    matrix.iter().enumerate().fold(0, |result, (i, row)| {
+        result | u64::from((row & value).count_ones() & 1 == 1) << i
+    })
+}
+
+// Invert `matrix` by Gauss-Jordan elimination, returning None if it's
+// singular or a row selects bits beyond the number of rows.
+pub(crate) fn invert(matrix: &[u64]) -> Option<Vec<u64>> {
+    let n = matrix.len();
+    if n == 0 || n > 64 || (n < 64 && matrix.iter().any(|row| row >> n != 0)) {
+        return None;
+    }
+
+    let mut rows = matrix.to_vec();
+    let mut inverse: Vec<u64> = (0..n).map(|i| 1 << i).collect();
+    for column in 0..n {
+        let pivot = (column..n).find(|&row| rows[row] >> column & 1 == 1)?;
+        rows.swap(column, pivot);
+        inverse.swap(column, pivot);
+        for row in 0..n {
+            if row != column && rows[row] >> column & 1 == 1 {
+                rows[row] ^= rows[column];
+                inverse[row] ^= inverse[column];
+            }
+        }
+    }
+    Some(inverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_undoes_the_transform() {
+        let mut state: u64 = 0x243F_6A88_85A3_08D3;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            state
+        };
+
+        let mut invertible = 0;
+        for bits in [8, 16, 32, 64] {
+            for _ in 0..50 {
+                let mask = u64::MAX >> (64 - bits);
+                let matrix: Vec<u64> = (0..bits).map(|_| next() & mask).collect();
+                let Some(inverse) = invert(&matrix) else {
+                    continue;
+                };
+                invertible += 1;
+                for _ in 0..20 {
+                    let value = next() & mask;
+                    assert_eq!(apply(&inverse, apply(&matrix, value)), value);
+                }
+            }
+        }
+        assert!(invertible > 0);
+
+        let identity: Vec<u64> = (0..8).map(|i| 1 << i).collect();
+        assert_eq!(invert(&identity), Some(identity.clone()));
+        assert_eq!(apply(&identity, 0xA5), 0xA5);
+
+        let mut singular = identity.clone();
+        singular[3] = singular[5];
+        assert_eq!(invert(&singular), None);
+        let mut too_wide = identity;
+        too_wide[0] |= 1 << 8;
+        assert_eq!(invert(&too_wide), None);
+    }
+}
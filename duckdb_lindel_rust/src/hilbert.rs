@@ -0,0 +1,264 @@
+// duckdb_lindel_rust
+// Copyright 2024 Rusty Conover <rusty@conover.me>
+// Licensed under the MIT License
+
+// A Hilbert curve for any number of bits per dimension.
+//
+// lindel only produces curves whose order is the full width of the coordinate
+// type, this uses the same algorithm by John Skilling but takes the number of
+// bits as a parameter, so a curve of e.g. three 40 bit dimensions can be
+// packed into a u128.
+//
+// The first dimension occupies the most significant bit of each group of
+// interleaved bits, which matches the layout produced by lindel.
+
+// Interleave the transposed form of a Hilbert index into a single integer.
+fn interleave(axes: &[u64], bits: u32) -> u128 {
+    let mut index: u128 = 0;
+    for bit in (0..bits).rev() {
+        for axis in axes {
+            index = (index << 1) | ((axis >> bit) & 1) as u128;
+        }
+    }
+    index
+}
+
+// Split an interleaved Hilbert index back into its transposed form.
+fn deinterleave(index: u128, bits: u32, axes: &mut [u64]) {
+    axes.iter_mut().for_each(|axis| *axis = 0);
+    let dims = axes.len() as u32;
+    for bit in 0..bits {
+        for (i, axis) in axes.iter_mut().enumerate() {
+            let position = bit * dims + (dims - 1 - i as u32);
+            *axis |= ((index >> position) & 1) as u64 * (1 << bit);
+        }
+    }
+}
+
+// Compute the Hilbert index of a point whose coordinates use `bits` bits each.
+//
+// The coordinates are used as scratch space.
+pub(crate) fn axes_to_index(axes: &mut [u64], bits: u32) -> u128 {
+    let dims = axes.len();
+    if dims == 0 || bits == 0 {
+        return 0;
+    }
+
+    // Inverse undo
+    for q_bit in (1..bits).rev() {
+        let q = 1u64 << q_bit;
+        let p = q - 1;
+        for i in 0..dims {
+            if axes[i] & q != 0 {
+                axes[0] ^= p;
+            } else {
+                let t = (axes[0] ^ axes[i]) & p;
+                axes[0] ^= t;
+                axes[i] ^= t;
+            }
+        }
+    }
+
+    // Gray encode
+    for i in 1..dims {
+        axes[i] ^= axes[i - 1];
+    }
+    let mut t = 0;
+    for q_bit in (1..bits).rev() {
+        let q = 1u64 << q_bit;
+        if axes[dims - 1] & q != 0 {
+            t ^= q - 1;
+        }
+    }
+    for axis in axes.iter_mut() {
+        *axis ^= t;
+    }
+
+    interleave(axes, bits)
+}
+
+// Compute the coordinates of a point from its Hilbert index, the inverse of
+// axes_to_index().
+pub(crate) fn index_to_axes(index: u128, bits: u32, axes: &mut [u64]) {
+    let dims = axes.len();
+    if dims == 0 {
+        return;
+    }
+    deinterleave(index, bits, axes);
+    if bits == 0 {
+        return;
+    }
+
+    // Gray decode
+    let t = axes[dims - 1] >> 1;
+    for i in (1..dims).rev() {
+        axes[i] ^= axes[i - 1];
+    }
+    axes[0] ^= t;
+
+    // Undo excess work
+    for q_bit in 1..bits {
+        let q = 1u64 << q_bit;
+        let p = q - 1;
+        for i in (0..dims).rev() {
+            if axes[i] & q != 0 {
+                axes[0] ^= p;
+            } else {
+                let t = (axes[0] ^ axes[i]) & p;
+                axes[0] ^= t;
+                axes[i] ^= t;
+            }
+        }
+    }
+}
+
+// Rotate and flip a quadrant of a 2-D curve of side `n` appropriately.
+fn rotate(n: u64, x: &mut u64, y: &mut u64, rx: u64, ry: u64) {
+    if ry == 0 {
+        if rx == 1 {
+            *x = n - 1 - *x;
+            *y = n - 1 - *y;
+        }
+        std::mem::swap(x, y);
+    }
+}
+
+// The classic 2-D xy2d() of a curve of side 2^order.  The curve starts at
+// (0, 0), heads along the x axis first and finishes at (2^order - 1, 0), which
+// is the same curve that axes_to_index() produces for [x, y].
+pub(crate) fn xy_to_d(order: u32, mut x: u64, mut y: u64) -> u64 {
+    let n = 1u64 << order;
+    let mut d = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        d += s * s * ((3 * rx) ^ ry);
+        rotate(n, &mut x, &mut y, rx, ry);
+        s /= 2;
+    }
+    d
+}
+
+// The inverse of xy_to_d().
+pub(crate) fn d_to_xy(order: u32, d: u64) -> (u64, u64) {
+    let n = 1u64 << order;
+    let (mut x, mut y) = (0, 0);
+    let mut t = d;
+    let mut s = 1;
+    while s < n {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+        rotate(s, &mut x, &mut y, rx, ry);
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+    (x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_lindel_at_full_width() {
+        let mut state: u64 = 0x853C_49E6_748F_EA9B;
+        for _ in 0..500 {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let r = state;
+
+            let values = [r as u32, (r >> 32) as u32];
+            let mut axes = values.map(u64::from);
+            assert_eq!(
+                axes_to_index(&mut axes, 32),
+                lindel::hilbert_encode(values) as u128
+            );
+
+            let values = [r as u8, (r >> 8) as u8, (r >> 16) as u8];
+            let mut axes = values.map(u64::from);
+            assert_eq!(
+                axes_to_index(&mut axes, 8),
+                lindel::hilbert_encode(values) as u128
+            );
+
+            let values = [
+                r as u16,
+                (r >> 16) as u16,
+                (r >> 32) as u16,
+                (r >> 48) as u16,
+            ];
+            let mut axes = values.map(u64::from);
+            assert_eq!(
+                axes_to_index(&mut axes, 16),
+                lindel::hilbert_encode(values) as u128
+            );
+
+            let values = [r, r.rotate_left(29)];
+            let mut axes = values;
+            let index = axes_to_index(&mut axes, 64);
+            assert_eq!(index, lindel::hilbert_encode(values));
+            index_to_axes(index, 64, &mut axes);
+            assert_eq!(axes, values);
+        }
+    }
+
+    #[test]
+    fn round_trips_at_partial_width() {
+        for bits in 1..=10 {
+            let mut seen = std::collections::HashSet::new();
+            for x in 0..(1u64 << bits).min(64) {
+                for y in 0..(1u64 << bits).min(64) {
+                    let mut axes = [x, y];
+                    let index = axes_to_index(&mut axes, bits);
+                    assert!(index < 1 << (2 * bits));
+                    assert!(seen.insert(index));
+                    index_to_axes(index, bits, &mut axes);
+                    assert_eq!(axes, [x, y]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn classic_2d_curve_of_order_2() {
+        let cells = [
+            (0, 0),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (1, 3),
+            (1, 2),
+            (2, 2),
+            (2, 3),
+            (3, 3),
+            (3, 2),
+            (3, 1),
+            (2, 1),
+            (2, 0),
+            (3, 0),
+        ];
+        for (d, (x, y)) in cells.into_iter().enumerate() {
+            assert_eq!(d_to_xy(2, d as u64), (x, y));
+            assert_eq!(xy_to_d(2, x, y), d as u64);
+        }
+
+        for (x, y) in [(0, 0), (123_456, 7), (u32::MAX as u64, u32::MAX as u64)] {
+            assert_eq!(d_to_xy(32, xy_to_d(32, x, y)), (x, y));
+        }
+
+        for order in 1..=6 {
+            for x in 0..1 << order {
+                for y in 0..1 << order {
+                    let d = xy_to_d(order, x, y);
+                    assert_eq!(axes_to_index(&mut [x, y], order), d as u128);
+                }
+            }
+        }
+    }
+}
@@ -4,7 +4,714 @@
 
 use std::ffi::c_void;
 
+mod gf2;
+mod hilbert;
+mod morton;
+
+// Provided by DuckDB when the extension is loaded.
+extern "C" {
+    ///Free a value returned from `duckdb_malloc`, `duckdb_value_varchar`, `duckdb_value_blob`, or `duckdb_value_string`.
+    ///
+    /// ptr: The memory region to de-allocate.
+    pub fn duckdb_free(ptr: *mut c_void);
+
+    ///Allocate `size` bytes of memory using the duckdb internal malloc function. Any memory allocated in this manner should be freed using `duckdb_free`.
+    ///
+    /// size: The number of bytes to allocate.  returns: A pointer to the allocated memory region.
+    pub fn duckdb_malloc(size: usize) -> *mut c_void;
+}
+
+/// Status codes returned by the checked entry points.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LindelStatus {
+    Ok = 0,
+    InvalidEncodingType = -1,
+    InvalidElementBitWidth = -2,
+    InvalidDimensions = -3,
+    /// The key has significant bits beyond what the requested shape can hold.
+    KeyOutOfRange = -4,
+    /// The source and destination buffers overlap.
+    OverlappingBuffers = -5,
+    /// An unexpected panic was caught before it could unwind into DuckDB.
+    Panic = -6,
+    /// The requested number of significant bits isn't valid for the shape.
+    InvalidPrecision = -7,
+    /// An input value has bits set beyond the requested precision.
+    ValueOutOfRange = -8,
+    /// The logical type isn't known or doesn't exist at the element width.
+    InvalidLogicalType = -9,
+    /// A floating point value is NaN or infinite and was asked to be rejected.
+    NonFiniteValue = -10,
+    /// The destination is too small for the key.
+    BufferTooSmall = -11,
+    /// A bit transform matrix isn't invertible.
+    SingularMatrix = -12,
+    /// The byte order is neither little nor big endian.
+    InvalidByteOrder = -13,
+    /// A required pointer argument is null.
+    NullPointer = -14,
+    /// A tagged key was encoded with a different shape than was requested.
+    ShapeMismatch = -15,
+}
+
+/// The type of the column a key was encoded from, which `perform_decode_typed()`
+/// validates against the element width.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LindelLogicalType {
+    Unsigned = 0,
+    /// Two's complement signed integers of the element width.
+    Signed = 1,
+    /// IEEE 754 floats, only valid for 32 and 64 bit elements.
+    Float = 2,
+}
+
+impl LindelLogicalType {
+    fn from_tag(logical_type: u8, element_bit_width: u8) -> Option<Self> {
+        match (logical_type, element_bit_width) {
+            (0, _) => Some(LindelLogicalType::Unsigned),
+            (1, _) => Some(LindelLogicalType::Signed),
+            (2, 32 | 64) => Some(LindelLogicalType::Float),
+            _ => None,
+        }
+    }
+}
+
+/// A signed 128 bit integer laid out like DuckDB's `hugeint_t`, since C has
+/// no 128 bit integer type to pass across the FFI boundary.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LindelHugeint {
+    pub lower: u64,
+    pub upper: i64,
+}
+
+impl From<i128> for LindelHugeint {
+    fn from(value: i128) -> Self {
+        LindelHugeint {
+            lower: value as u64,
+            upper: (value >> 64) as i64,
+        }
+    }
+}
+
+impl From<LindelHugeint> for i128 {
+    fn from(value: LindelHugeint) -> i128 {
+        (value.upper as i128) << 64 | value.lower as i128
+    }
+}
+
+// Functions that return a value rather than a status use an i32 where
+// non-negative values are results and negative values are a LindelStatus.
+impl From<LindelStatus> for i32 {
+    fn from(status: LindelStatus) -> i32 {
+        status as i32
+    }
+}
+
+// Run the body of an exported function, converting any panic into a status
+// code, since unwinding across the FFI boundary into DuckDB isn't allowed.
+fn ffi_boundary<T: From<LindelStatus>, F: FnOnce() -> T>(f: F) -> T {
+    ffi_boundary_or(LindelStatus::Panic.into(), f)
+}
+
+// Like ffi_boundary() for functions that can't return a status, returning
+// `on_panic` instead.
+fn ffi_boundary_or<T, F: FnOnce() -> T>(on_panic: T, f: F) -> T {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or(on_panic)
+}
+
+// The number of bits in the integer that lindel packs `dims` values of
+// `element_bit_width` bits into, this mirrors the `IdealKey` table of the
+// morton-encoding crate. Returns None for shapes that are not supported.
+fn key_bits(element_bit_width: u8, dims: usize) -> Option<usize> {
+    if !matches!(element_bit_width, 8 | 16 | 32 | 64) || dims == 0 {
+        return None;
+    }
+    match (element_bit_width as usize).checked_mul(dims) {
+        Some(total_bits) if total_bits <= 128 => Some(total_bits.next_power_of_two()),
+        _ => None,
+    }
+}
+
+// Read an encoded key of `bits` width from an unaligned source pointer.
+unsafe fn read_key(src: *const c_void, bits: usize) -> u128 {
+    match bits {
+        8 => std::ptr::read_unaligned(src as *const u8) as u128,
+        16 => std::ptr::read_unaligned(src as *const u16) as u128,
+        32 => std::ptr::read_unaligned(src as *const u32) as u128,
+        64 => std::ptr::read_unaligned(src as *const u64) as u128,
+        128 => std::ptr::read_unaligned(src as *const u128),
+        _ => unreachable!("key_bits() only returns power of two widths"),
+    }
+}
+
+// Read a single lane of decoded values, widened to a u64.
+unsafe fn read_lane(ptr: *const c_void, element_bit_width: u8, index: usize) -> u64 {
+    match element_bit_width {
+        8 => std::ptr::read_unaligned((ptr as *const u8).add(index)) as u64,
+        16 => std::ptr::read_unaligned((ptr as *const u16).add(index)) as u64,
+        32 => std::ptr::read_unaligned((ptr as *const u32).add(index)) as u64,
+        64 => std::ptr::read_unaligned((ptr as *const u64).add(index)),
+        _ => panic!("Invalid element bit width"),
+    }
+}
+
+// Write a single lane of decoded values, truncating to the element width.
+unsafe fn write_lane(ptr: *mut c_void, element_bit_width: u8, index: usize, value: u64) {
+    match element_bit_width {
+        8 => std::ptr::write_unaligned((ptr as *mut u8).add(index), value as u8),
+        16 => std::ptr::write_unaligned((ptr as *mut u16).add(index), value as u16),
+        32 => std::ptr::write_unaligned((ptr as *mut u32).add(index), value as u32),
+        64 => std::ptr::write_unaligned((ptr as *mut u64).add(index), value),
+        _ => panic!("Invalid element bit width"),
+    }
+}
+
+// Decode a key that has already been read into a u128, the shape must
+// already have been validated.
+fn decode_key(
+    encoding_type: u8,
+    element_bit_width: u8,
+    dims: usize,
+    key: u128,
+    dest: *mut c_void,
+) -> LindelStatus {
+    macro_rules! decode_as {
+        ($key_type:ty) => {{
+            let key = key as $key_type;
+            perform_decode(
+                encoding_type,
+                element_bit_width,
+                &key as *const $key_type as *const c_void,
+                dest,
+                dims,
+            )
+        }};
+    }
+    match key_bits(element_bit_width, dims) {
+        Some(8) => decode_as!(u8),
+        Some(16) => decode_as!(u16),
+        Some(32) => decode_as!(u32),
+        Some(64) => decode_as!(u64),
+        Some(128) => decode_as!(u128),
+        _ => LindelStatus::InvalidDimensions,
+    }
+}
+
+// Store an encoded key into an unaligned destination of `bits` width.
+unsafe fn write_key(dest: *mut c_void, bits: usize, key: u128) {
+    match bits {
+        8 => std::ptr::write_unaligned(dest as *mut u8, key as u8),
+        16 => std::ptr::write_unaligned(dest as *mut u16, key as u16),
+        32 => std::ptr::write_unaligned(dest as *mut u32, key as u32),
+        64 => std::ptr::write_unaligned(dest as *mut u64, key as u64),
+        128 => std::ptr::write_unaligned(dest as *mut u128, key),
+        _ => unreachable!("keys are always stored in power of two widths"),
+    }
+}
+
+// Validate a shape of `dims` values of `bits_per_dim` bits, returning the
+// number of bits in the smallest integer that holds the key.
+fn checked_bits_shape(dims: usize, bits_per_dim: u8) -> Result<usize, LindelStatus> {
+    if !(1..=64).contains(&bits_per_dim) {
+        return Err(LindelStatus::InvalidPrecision);
+    }
+    match dims.checked_mul(bits_per_dim as usize) {
+        Some(total_bits) if dims > 0 && total_bits <= 128 => {
+            Ok(total_bits.next_power_of_two().max(8))
+        }
+        _ => Err(LindelStatus::InvalidDimensions),
+    }
+}
+
+// Validate the element width and dimension count of a key, returning the
+// number of bits in the integer it is stored in.
+fn checked_key_bits(element_bit_width: u8, dims: usize) -> Result<usize, LindelStatus> {
+    if !matches!(element_bit_width, 8 | 16 | 32 | 64) {
+        return Err(LindelStatus::InvalidElementBitWidth);
+    }
+    key_bits(element_bit_width, dims).ok_or(LindelStatus::InvalidDimensions)
+}
+
+// Read a key of the given shape, verifying that no bits are set above
+// `dims * element_bit_width`.
+//
+// Both a 3 and a 4 dimension key of 32-bit elements are stored as a u128,
+// so the key type alone can't tell them apart.  A key with set bits above
+// `dims * element_bit_width` can't have come from an encode of that shape,
+// so it is rejected rather than decoded into garbage.  A key produced from a
+// different dimension count whose extra high bits happen to be zero can't be
+// detected this way, keys from lindel_encode_tagged() carry their shape so
+// perform_decode_tagged() can always tell.
+unsafe fn read_checked_key(
+    element_bit_width: u8,
+    dims: usize,
+    src: *const c_void,
+) -> Result<u128, LindelStatus> {
+    let bits = checked_key_bits(element_bit_width, dims)?;
+    assert!(!src.is_null());
+    let key = read_key(src, bits);
+    let used_bits = element_bit_width as usize * dims;
+    if used_bits < 128 && key >> used_bits != 0 {
+        return Err(LindelStatus::KeyOutOfRange);
+    }
+    Ok(key)
+}
+
+// Validate the shape of a decode request and return the encoded key.
+unsafe fn checked_key(
+    encoding_type: u8,
+    element_bit_width: u8,
+    src: *const c_void,
+    dims: usize,
+) -> Result<u128, LindelStatus> {
+    if encoding_type > 1 {
+        return Err(LindelStatus::InvalidEncodingType);
+    }
+    read_checked_key(element_bit_width, dims, src)
+}
+
+// Determine if the encoded key at `src` overlaps the `dest_len` decoded values
+// that will be written to `dest`, the shape must already have been validated.
+fn buffers_overlap(
+    element_bit_width: u8,
+    src: *const c_void,
+    dest: *mut c_void,
+    dest_len: usize,
+) -> bool {
+    let src_start = src as usize;
+    let src_end = src_start + key_bits(element_bit_width, dest_len).unwrap_or(0) / 8;
+    let dest_start = dest as usize;
+    let dest_end = dest_start + dest_len * (element_bit_width as usize / 8);
+    src_start < dest_end && dest_start < src_end
+}
+
+/// Decode an encoded value like `perform_decode()` but verify that the key
+/// could have been produced by an encode of `dest_len` dimensions first.
+///
+/// Only set bits beyond the shape can be detected, so a key of more
+/// dimensions whose extra high bits are zero still decodes.  Use
+/// `lindel_encode_tagged()` and `perform_decode_tagged()` when a decode of
+/// the wrong shape must always be rejected.
+///
+/// Since the decode writes into `dest` while still reading from `src`,
+/// overlapping buffers are also rejected rather than producing corrupt output.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_checked(
+    encoding_type: u8,
+    element_bit_width: u8,
+    src: *const c_void,
+    dest: *mut c_void,
+    dest_len: usize,
+) -> LindelStatus {
+    ffi_boundary(
+        || match checked_key(encoding_type, element_bit_width, src, dest_len) {
+            Ok(_) if buffers_overlap(element_bit_width, src, dest, dest_len) => {
+                LindelStatus::OverlappingBuffers
+            }
+            Ok(_) => perform_decode(encoding_type, element_bit_width, src, dest, dest_len),
+            Err(status) => status,
+        },
+    )
+}
+
+// The tag byte that records the shape of a key from lindel_encode_tagged(),
+// the encoding type in the top two bits, the element width as 0 to 3 for 8
+// to 64 bits in the next two and the number of dimensions less one in the
+// low four.  The shape must already have been validated.
+fn shape_tag(encoding_type: u8, element_bit_width: u8, dims: usize) -> u8 {
+    encoding_type << 6 | (element_bit_width.trailing_zeros() as u8 - 3) << 4 | (dims as u8 - 1)
+}
+
+/// Encode `dims` values of `element_bit_width` bits like `encode_any()`, but
+/// prefix the key with a byte that records the encoding type, element width
+/// and number of dimensions, so `perform_decode_tagged()` can reject a decode
+/// of a different shape.
+///
+/// The plain keys of e.g. three and four u32 values are both stored in a
+/// u128, so a decode of the wrong number of dimensions can't always be
+/// detected from the key alone.  The tagged key is the tag byte followed by
+/// the key in native byte order, `1 + key bytes` in all, and `result` doesn't
+/// need to be aligned.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.  `BufferTooSmall` is returned if `result_len` is
+/// less than the size of the tagged key.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_encode_tagged(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const u8,
+    dims: usize,
+    result: *mut u8,
+    result_len: usize,
+) -> i32 {
+    if encoding_type > 1 {
+        return LindelStatus::InvalidEncodingType.into();
+    }
+    let bits = match checked_key_bits(element_bit_width, dims) {
+        Ok(bits) => bits,
+        Err(status) => return status.into(),
+    };
+    if values.is_null() || result.is_null() {
+        return LindelStatus::NullPointer.into();
+    }
+    if result_len < 1 + bits / 8 {
+        return LindelStatus::BufferTooSmall.into();
+    }
+
+    let written = encode_any(
+        encoding_type,
+        element_bit_width,
+        values,
+        dims,
+        result.add(1) as *mut c_void,
+    );
+    if written < 0 {
+        return written;
+    }
+    *result = shape_tag(encoding_type, element_bit_width, dims);
+    written + 1
+}
+
+/// Decode a key produced by `lindel_encode_tagged()` from the `src_len`
+/// bytes at `src` into `dest_len` values of `element_bit_width` bits.
+///
+/// Returns `ShapeMismatch` if the key was encoded with a different encoding
+/// type, element width or number of dimensions, or if `src_len` isn't the
+/// size of a tagged key of that shape, in which case `dest` is left
+/// unchanged.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_tagged(
+    encoding_type: u8,
+    element_bit_width: u8,
+    src: *const u8,
+    src_len: usize,
+    dest: *mut c_void,
+    dest_len: usize,
+) -> LindelStatus {
+    if encoding_type > 1 {
+        return LindelStatus::InvalidEncodingType;
+    }
+    let bits = match checked_key_bits(element_bit_width, dest_len) {
+        Ok(bits) => bits,
+        Err(status) => return status,
+    };
+    if src.is_null() || dest.is_null() {
+        return LindelStatus::NullPointer;
+    }
+    if src_len != 1 + bits / 8 || *src != shape_tag(encoding_type, element_bit_width, dest_len) {
+        return LindelStatus::ShapeMismatch;
+    }
+
+    ffi_boundary(|| {
+        let key = read_key(src.add(1) as *const c_void, bits);
+        decode_key(encoding_type, element_bit_width, dest_len, key, dest)
+    })
+}
+
+/// Decode a key of `dest_len` values of `element_bit_width` bits into lanes
+/// of `dest_bit_width` bits, verifying that no value loses significant bits
+/// when it's narrowed.
+///
+/// Returns `ValueOutOfRange` if a decoded value doesn't fit in
+/// `dest_bit_width` bits, e.g. a value above 255 of a key encoded from u16
+/// values decoded into u8 lanes, in which case `dest` is left unchanged.
+/// `dest` doesn't need to be aligned.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_narrowing(
+    encoding_type: u8,
+    element_bit_width: u8,
+    dest_bit_width: u8,
+    src: *const c_void,
+    dest: *mut c_void,
+    dest_len: usize,
+) -> LindelStatus {
+    if !matches!(dest_bit_width, 8 | 16 | 32 | 64) {
+        return LindelStatus::InvalidElementBitWidth;
+    }
+
+    ffi_boundary(|| {
+        let key = match checked_key(encoding_type, element_bit_width, src, dest_len) {
+            Ok(key) => key,
+            Err(status) => return status,
+        };
+        let mut values = [0u128; 1];
+        let values = values.as_mut_ptr() as *mut c_void;
+        let status = decode_key(encoding_type, element_bit_width, dest_len, key, values);
+        if status != LindelStatus::Ok {
+            return status;
+        }
+
+        if dest_bit_width < 64
+            && (0..dest_len).any(|i| read_lane(values, element_bit_width, i) >> dest_bit_width != 0)
+        {
+            return LindelStatus::ValueOutOfRange;
+        }
+        assert!(!dest.is_null());
+        for i in 0..dest_len {
+            write_lane(
+                dest,
+                dest_bit_width,
+                i,
+                read_lane(values, element_bit_width, i),
+            );
+        }
+        LindelStatus::Ok
+    })
+}
+
+/// Decode a key of `dims` values of `element_bit_width` bits and write only
+/// the dimensions whose bit is set in `dim_mask` to `dest`, packed one after
+/// another in dimension order, e.g. a `dim_mask` of `0b0101` writes dimension
+/// 0 then dimension 2.
+///
+/// `dest` must hold `dim_mask.count_ones()` values and doesn't need to be
+/// aligned.  A `dim_mask` that selects a dimension beyond `dims` returns
+/// `InvalidDimensions`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_subset(
+    encoding_type: u8,
+    element_bit_width: u8,
+    dims: usize,
+    dim_mask: u32,
+    src: *const c_void,
+    dest: *mut c_void,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        let key = match checked_key(encoding_type, element_bit_width, src, dims) {
+            Ok(key) => key,
+            Err(status) => return status,
+        };
+        if dims < 32 && dim_mask >> dims != 0 {
+            return LindelStatus::InvalidDimensions;
+        }
+        let mut values = [0u128; 1];
+        let values = values.as_mut_ptr() as *mut c_void;
+        let status = decode_key(encoding_type, element_bit_width, dims, key, values);
+        if status != LindelStatus::Ok {
+            return status;
+        }
+
+        assert!(!dest.is_null());
+        let selected = (0..dims).filter(|i| dim_mask & (1 << i) != 0);
+        for (position, i) in selected.enumerate() {
+            let value = read_lane(values, element_bit_width, i);
+            write_lane(dest, element_bit_width, position, value);
+        }
+        LindelStatus::Ok
+    })
+}
+
+/// Decode a key of exactly `dims` values of `element_bit_width` bits.
+///
+/// The number of dimensions is authoritative and the key is read from the
+/// integer an encode of that shape produces, `dims * element_bit_width` bits
+/// rounded up to 8, 16, 32, 64 or 128, so e.g. a 64 bit key of 16 bit
+/// elements is never guessed to be 3 or 4 values.  A key with bits set beyond
+/// what `dims` values occupy is rejected with `KeyOutOfRange`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_explicit(
+    encoding_type: u8,
+    element_bit_width: u8,
+    dims: u8,
+    src: *const c_void,
+    dest: *mut c_void,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        let dims = dims as usize;
+        match checked_key(encoding_type, element_bit_width, src, dims) {
+            Ok(key) => decode_key(encoding_type, element_bit_width, dims, key, dest),
+            Err(status) => status,
+        }
+    })
+}
+
+/// Decode a key of `dims` values of `element_bit_width` bits into a buffer
+/// allocated with `duckdb_malloc()`, so the caller doesn't need to size the
+/// destination beforehand.  The number of values is written to `out_count`.
+///
+/// Returns null, with `out_count` set to zero, if the key can't be decoded.
+/// The caller owns the buffer and must release it with `duckdb_free()`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_alloc(
+    encoding_type: u8,
+    element_bit_width: u8,
+    dims: usize,
+    src: *const c_void,
+    out_count: *mut usize,
+) -> *mut c_void {
+    if out_count.is_null() {
+        return std::ptr::null_mut();
+    }
+    *out_count = 0;
+
+    let dest = ffi_boundary_or(None, || {
+        let key = checked_key(encoding_type, element_bit_width, src, dims).ok()?;
+        let dest = duckdb_malloc(dims * element_bit_width as usize / 8);
+        if dest.is_null() {
+            return None;
+        }
+        if decode_key(encoding_type, element_bit_width, dims, key, dest) != LindelStatus::Ok {
+            duckdb_free(dest);
+            return None;
+        }
+        Some(dest)
+    });
+
+    match dest {
+        Some(dest) => {
+            *out_count = dims;
+            dest
+        }
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Decode a key of `dims` values of `element_bit_width` bits and format the
+/// unsigned values as a NUL terminated string like `[1, 2, 3]`, a one call
+/// sanity check when diagnosing an encode or decode.
+///
+/// Returns null if the key can't be decoded.  The string is allocated with
+/// `duckdb_malloc()`, the caller owns it and must release it with
+/// `duckdb_free()`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_debug(
+    encoding_type: u8,
+    element_bit_width: u8,
+    dims: usize,
+    src: *const c_void,
+) -> *mut std::ffi::c_char {
+    ffi_boundary_or(std::ptr::null_mut(), || {
+        let key = match checked_key(encoding_type, element_bit_width, src, dims) {
+            Ok(key) => key,
+            Err(_) => return std::ptr::null_mut(),
+        };
+        let mut values = [0u128; 1];
+        let values = values.as_mut_ptr() as *mut c_void;
+        if decode_key(encoding_type, element_bit_width, dims, key, values) != LindelStatus::Ok {
+            return std::ptr::null_mut();
+        }
+        let values: Vec<String> = (0..dims)
+            .map(|i| read_lane(values, element_bit_width, i).to_string())
+            .collect();
+        let text = format!("[{}]", values.join(", "));
+
+        let dest = duckdb_malloc(text.len() + 1) as *mut u8;
+        if !dest.is_null() {
+            std::ptr::copy_nonoverlapping(text.as_ptr(), dest, text.len());
+            *dest.add(text.len()) = 0;
+        }
+        dest as *mut std::ffi::c_char
+    })
+}
+
+/// Decode an encoded value like `perform_decode()` for a column of the
+/// `LindelLogicalType` given by `logical_type`, so the SQL layer can decode
+/// straight into the type of the column that was encoded.
+///
+/// The encode functions take the bits of signed and floating point values as
+/// they are, so decoding the bits unchanged already gives exactly the values
+/// that were encoded.  Apart from `centered`, the logical type is therefore
+/// only validated, `InvalidLogicalType` is returned for a type that doesn't
+/// exist at the element width, and every valid type writes the same bits.
+///
+/// With `centered` set, the values are taken to be coordinates of a grid
+/// centered on zero whose sign bits were flipped before encoding, such as the
+/// keys of `lindel_encode_offset()` with zero offsets, and decode to signed
+/// values in `[-2^(w-1), 2^(w-1) - 1]`.  Only the `Signed` logical type can be
+/// centered.
+///
+/// Returns `NullPointer` if `src` or `dest` is null.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_typed(
+    encoding_type: u8,
+    element_bit_width: u8,
+    logical_type: u8,
+    centered: bool,
+    src: *const c_void,
+    dest: *mut c_void,
+    dest_len: usize,
+) -> LindelStatus {
+    if !matches!(element_bit_width, 8 | 16 | 32 | 64) {
+        return LindelStatus::InvalidElementBitWidth;
+    }
+    let logical_type = match LindelLogicalType::from_tag(logical_type, element_bit_width) {
+        Some(LindelLogicalType::Signed) => LindelLogicalType::Signed,
+        Some(_) if centered => return LindelStatus::InvalidLogicalType,
+        Some(logical_type) => logical_type,
+        None => return LindelStatus::InvalidLogicalType,
+    };
+    if src.is_null() || dest.is_null() {
+        return LindelStatus::NullPointer;
+    }
+    if logical_type != LindelLogicalType::Signed || !centered {
+        return perform_decode(encoding_type, element_bit_width, src, dest, dest_len);
+    }
+
+    ffi_boundary(|| {
+        let mut values = [0u128; 1];
+        let values = values.as_mut_ptr() as *mut c_void;
+        let status = perform_decode(encoding_type, element_bit_width, src, values, dest_len);
+        if status != LindelStatus::Ok {
+            return status;
+        }
+        let sign_bit = 1 << (element_bit_width - 1);
+        for i in 0..dest_len {
+            let value = read_lane(values, element_bit_width, i);
+            write_lane(values, element_bit_width, i, value ^ sign_bit);
+        }
+        std::ptr::copy_nonoverlapping(
+            values as *const u8,
+            dest as *mut u8,
+            dest_len * element_bit_width as usize / 8,
+        );
+        LindelStatus::Ok
+    })
+}
+
 // Decode an encoded value and store it in the destination pointer.
+//
+// Returns LindelStatus::InvalidDimensions if the number of dimensions isn't
+// supported for the element width.
 #[no_mangle]
 pub extern "C" fn perform_decode(
     encoding_type: u8,
@@ -12,7 +719,9 @@ pub extern "C" fn perform_decode(
     src: *const c_void,
     dest: *mut c_void,
     dest_len: usize,
-) {
+) -> LindelStatus {
+    // lindel decodes from the `IdealKey` of the element type and number of
+    // dimensions, so a `$src_type` that doesn't match fails to compile.
     macro_rules! decode_and_copy {
         ($dest_type: ty, $src_type:ty, $len:expr) => {{
             unsafe {
@@ -22,7 +731,17 @@ pub extern "C" fn perform_decode(
                     1 => lindel::morton_decode,
                     _ => panic!("Invalid encoding type"),
                 };
-                let values: [$dest_type; $len] = function(*(src as *const $src_type));
+                let key = *(src as *const $src_type);
+                // Two and three dimensional Morton keys have a faster table
+                // driven decode that produces the same values.
+                let table_values = match encoding_type {
+                    1 => morton::decode::<$len>(key as u128, <$dest_type>::BITS),
+                    _ => None,
+                };
+                let values: [$dest_type; $len] = match table_values {
+                    Some(values) => values.map(|value| value as $dest_type),
+                    None => function(key),
+                };
                 for i in 0..$len {
                     *dest_ptr.add(i) = values[i];
                 }
@@ -30,313 +749,6311 @@ pub extern "C" fn perform_decode(
         }};
     }
 
-    match element_bit_width {
-        8 => match dest_len {
-            1 => decode_and_copy!(u8, u8, 1),
-            2 => decode_and_copy!(u8, u16, 2),
-            3 => decode_and_copy!(u8, u32, 3),
-            4 => decode_and_copy!(u8, u32, 4),
-            5 => decode_and_copy!(u8, u64, 5),
-            6 => decode_and_copy!(u8, u64, 6),
-            7 => decode_and_copy!(u8, u64, 7),
-            8 => decode_and_copy!(u8, u64, 8),
-            9 => decode_and_copy!(u8, u128, 9),
-            10 => decode_and_copy!(u8, u128, 10),
-            11 => decode_and_copy!(u8, u128, 11),
-            12 => decode_and_copy!(u8, u128, 12),
-            13 => decode_and_copy!(u8, u128, 13),
-            14 => decode_and_copy!(u8, u128, 14),
-            15 => decode_and_copy!(u8, u128, 15),
-            16 => decode_and_copy!(u8, u128, 16),
-            _ => panic!("Invalid length"),
-        },
-        16 => match dest_len {
-            1 => decode_and_copy!(u16, u16, 1),
-            2 => decode_and_copy!(u16, u32, 2),
-            3 => decode_and_copy!(u16, u64, 3),
-            4 => decode_and_copy!(u16, u64, 4),
-            5 => decode_and_copy!(u16, u128, 5),
-            6 => decode_and_copy!(u16, u128, 6),
-            7 => decode_and_copy!(u16, u128, 7),
-            8 => decode_and_copy!(u16, u128, 8),
-            _ => panic!("Invalid length"),
-        },
-        32 => match dest_len {
-            1 => decode_and_copy!(u32, u32, 1),
-            2 => decode_and_copy!(u32, u64, 2),
-            3 => decode_and_copy!(u32, u128, 3),
-            4 => decode_and_copy!(u32, u128, 4),
-            _ => panic!("Invalid length"),
-        },
-        64 => match dest_len {
-            1 => decode_and_copy!(u64, u64, 1),
-            2 => decode_and_copy!(u64, u128, 2),
-            _ => panic!("Invalid length"),
-        },
-        _ => panic!("Invalid element bit width"),
+    // Reject values that can never be decoded before starting any work.
+    if encoding_type > 1 {
+        return LindelStatus::InvalidEncodingType;
+    }
+    if !matches!(element_bit_width, 8 | 16 | 32 | 64) {
+        return LindelStatus::InvalidElementBitWidth;
+    }
+    if key_bits(element_bit_width, dest_len).is_none() {
+        return LindelStatus::InvalidDimensions;
     }
+
+    ffi_boundary(|| {
+        match element_bit_width {
+            8 => match dest_len {
+                1 => decode_and_copy!(u8, u8, 1),
+                2 => decode_and_copy!(u8, u16, 2),
+                3 => decode_and_copy!(u8, u32, 3),
+                4 => decode_and_copy!(u8, u32, 4),
+                5 => decode_and_copy!(u8, u64, 5),
+                6 => decode_and_copy!(u8, u64, 6),
+                7 => decode_and_copy!(u8, u64, 7),
+                8 => decode_and_copy!(u8, u64, 8),
+                9 => decode_and_copy!(u8, u128, 9),
+                10 => decode_and_copy!(u8, u128, 10),
+                11 => decode_and_copy!(u8, u128, 11),
+                12 => decode_and_copy!(u8, u128, 12),
+                13 => decode_and_copy!(u8, u128, 13),
+                14 => decode_and_copy!(u8, u128, 14),
+                15 => decode_and_copy!(u8, u128, 15),
+                16 => decode_and_copy!(u8, u128, 16),
+                _ => panic!("Invalid length"),
+            },
+            16 => match dest_len {
+                1 => decode_and_copy!(u16, u16, 1),
+                2 => decode_and_copy!(u16, u32, 2),
+                3 => decode_and_copy!(u16, u64, 3),
+                4 => decode_and_copy!(u16, u64, 4),
+                5 => decode_and_copy!(u16, u128, 5),
+                6 => decode_and_copy!(u16, u128, 6),
+                7 => decode_and_copy!(u16, u128, 7),
+                8 => decode_and_copy!(u16, u128, 8),
+                _ => panic!("Invalid length"),
+            },
+            32 => match dest_len {
+                1 => decode_and_copy!(u32, u32, 1),
+                2 => decode_and_copy!(u32, u64, 2),
+                3 => decode_and_copy!(u32, u128, 3),
+                4 => decode_and_copy!(u32, u128, 4),
+                _ => panic!("Invalid length"),
+            },
+            64 => match dest_len {
+                1 => decode_and_copy!(u64, u64, 1),
+                2 => decode_and_copy!(u64, u128, 2),
+                _ => panic!("Invalid length"),
+            },
+            _ => panic!("Invalid element bit width"),
+        }
+        LindelStatus::Ok
+    })
 }
 
-// Create a macro to handle the repetitive part
-macro_rules! encode_and_store {
-    ($function:expr, $array:expr, $type:ty, $result:expr) => {{
-        let calculated_result = $function($array);
-        let result_ptr = $result as *mut $type;
-        unsafe {
-            *result_ptr = calculated_result;
+/// Decode only the most significant `significant_bits` of a key, treating the
+/// remaining low bits as zero, and clear the bits of each value below that
+/// precision.  This yields the lower corner of the cell the truncated key
+/// represents.
+///
+/// Morton keys may be truncated to any number of bits, but a Hilbert cell is
+/// only axis aligned at whole levels of the curve, so for Hilbert keys
+/// `significant_bits` must be a multiple of `dest_len`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_prefix(
+    encoding_type: u8,
+    element_bit_width: u8,
+    significant_bits: u8,
+    src: *const c_void,
+    dest: *mut c_void,
+    dest_len: usize,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        let key = match checked_key(encoding_type, element_bit_width, src, dest_len) {
+            Ok(key) => key,
+            Err(status) => return status,
+        };
+        let used_bits = element_bit_width as usize * dest_len;
+        let significant_bits = significant_bits as usize;
+        if significant_bits > used_bits
+            || (encoding_type == 0 && !significant_bits.is_multiple_of(dest_len))
+        {
+            return LindelStatus::InvalidPrecision;
+        }
+
+        let dropped_bits = used_bits - significant_bits;
+        let key = if dropped_bits >= 128 {
+            0
+        } else {
+            key >> dropped_bits << dropped_bits
+        };
+        let status = decode_key(encoding_type, element_bit_width, dest_len, key, dest);
+        if status != LindelStatus::Ok {
+            return status;
+        }
+
+        // The first dimension occupies the most significant bit of each group
+        // of interleaved bits, so any remainder goes to the leading dimensions.
+        for i in 0..dest_len {
+            let kept_bits =
+                significant_bits / dest_len + usize::from(i < significant_bits % dest_len);
+            let cleared_bits = element_bit_width as usize - kept_bits;
+            let value = read_lane(dest, element_bit_width, i);
+            let value = if cleared_bits >= 64 {
+                0
+            } else {
+                value >> cleared_bits << cleared_bits
+            };
+            write_lane(dest, element_bit_width, i, value);
         }
-    }};
+        LindelStatus::Ok
+    })
 }
 
-macro_rules! generic_encode_u8_var {
-    ($func_name:ident, $encoding_expr: expr) => {
-        /// # Safety
-        ///
-        /// This function is unsafe because it dereferences raw pointers.
-        #[no_mangle]
-        pub unsafe extern "C" fn $func_name(ptr: *const u8, len: usize, result: *mut c_void) -> () {
-            let args = unsafe {
-                assert!(!ptr.is_null());
-                std::slice::from_raw_parts(ptr, len)
-            };
-
-            match args.len() {
-                1 => encode_and_store!($encoding_expr, [args[0]], u8, result),
-                2 => encode_and_store!($encoding_expr, [args[0], args[1]], u16, result),
-                3 => encode_and_store!($encoding_expr, [args[0], args[1], args[2]], u32, result),
-                4 => encode_and_store!(
-                    $encoding_expr,
-                    [args[0], args[1], args[2], args[3]],
-                    u32,
-                    result
-                ),
-                5 => encode_and_store!(
-                    $encoding_expr,
-                    [args[0], args[1], args[2], args[3], args[4]],
-                    u64,
-                    result
-                ),
-                6 => encode_and_store!(
-                    $encoding_expr,
-                    [args[0], args[1], args[2], args[3], args[4], args[5]],
-                    u64,
-                    result
-                ),
-                7 => encode_and_store!(
-                    $encoding_expr,
-                    [args[0], args[1], args[2], args[3], args[4], args[5], args[6]],
-                    u64,
-                    result
-                ),
-                8 => encode_and_store!(
-                    $encoding_expr,
-                    [args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7]],
-                    u64,
-                    result
-                ),
-                9 => encode_and_store!(
-                    $encoding_expr,
-                    [
-                        args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7],
-                        args[8]
-                    ],
-                    u128,
-                    result
-                ),
-                10 => encode_and_store!(
-                    $encoding_expr,
-                    [
-                        args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7],
-                        args[8], args[9]
-                    ],
-                    u128,
-                    result
-                ),
-                11 => encode_and_store!(
-                    $encoding_expr,
-                    [
-                        args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7],
-                        args[8], args[9], args[10]
-                    ],
-                    u128,
-                    result
-                ),
-                12 => encode_and_store!(
-                    $encoding_expr,
-                    [
-                        args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7],
-                        args[8], args[9], args[10], args[11]
-                    ],
-                    u128,
-                    result
-                ),
-                13 => encode_and_store!(
-                    $encoding_expr,
-                    [
-                        args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7],
-                        args[8], args[9], args[10], args[11], args[12]
-                    ],
-                    u128,
-                    result
-                ),
-                14 => encode_and_store!(
-                    $encoding_expr,
-                    [
-                        args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7],
-                        args[8], args[9], args[10], args[11], args[12], args[13]
-                    ],
-                    u128,
-                    result
-                ),
-                15 => encode_and_store!(
-                    $encoding_expr,
-                    [
-                        args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7],
-                        args[8], args[9], args[10], args[11], args[12], args[13], args[14]
-                    ],
-                    u128,
-                    result
-                ),
-                16 => encode_and_store!(
-                    $encoding_expr,
-                    [
-                        args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7],
-                        args[8], args[9], args[10], args[11], args[12], args[13], args[14],
-                        args[16]
-                    ],
-                    u128,
-                    result
-                ),
-                _ => panic!("Invalid length"),
-            }
+/// Compute the bounding box of the Hilbert cell that a key represents at a
+/// precision of `level_bits` interleaved bits.
+///
+/// The lower corner is written to `min_out` and the upper, inclusive, corner
+/// to `max_out`, both as `dims` values of `element_bit_width` bits.  A cell is
+/// `2^(element_bit_width - level_bits / dims)` wide in every dimension, so
+/// `level_bits` must be a multiple of `dims`.
+///
+/// Returns `NullPointer` if `key`, `min_out` or `max_out` is null.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_cell_bounds(
+    key: *const c_void,
+    element_bit_width: u8,
+    dims: usize,
+    level_bits: u8,
+    min_out: *mut c_void,
+    max_out: *mut c_void,
+) -> LindelStatus {
+    if key.is_null() || min_out.is_null() || max_out.is_null() {
+        return LindelStatus::NullPointer;
+    }
+
+    ffi_boundary(|| {
+        let status = perform_decode_prefix(0, element_bit_width, level_bits, key, min_out, dims);
+        if status != LindelStatus::Ok {
+            return status;
         }
-    };
+
+        let cell_bits = element_bit_width as usize - level_bits as usize / dims;
+        let cell_mask = ((1u128 << cell_bits) - 1) as u64;
+        for i in 0..dims {
+            let min = read_lane(min_out, element_bit_width, i);
+            write_lane(max_out, element_bit_width, i, min | cell_mask);
+        }
+        LindelStatus::Ok
+    })
 }
 
-generic_encode_u8_var!(hilbert_encode_u8_var, lindel::hilbert_encode);
-generic_encode_u8_var!(morton_encode_u8_var, lindel::morton_encode);
+/// Compute the inclusive range of full precision keys of `total_bits` bits
+/// that share the `significant_bits` prefix in `prefix_key`, for range scans
+/// at a coarser precision.
+///
+/// Every key with the same prefix lies within the one cell and the curve
+/// visits the cell contiguously, so the range is `prefix << cleared` to
+/// `(prefix << cleared) | (2^cleared - 1)` where `cleared` is
+/// `total_bits - significant_bits`.  `prefix_key` holds the prefix in its low
+/// `significant_bits` bits, and the keys and prefix are unaligned u128s.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_prefix_range(
+    prefix_key: *const c_void,
+    significant_bits: u8,
+    total_bits: u8,
+    min_out: *mut c_void,
+    max_out: *mut c_void,
+) -> LindelStatus {
+    if total_bits == 0 || total_bits > 128 || significant_bits > total_bits {
+        return LindelStatus::InvalidPrecision;
+    }
 
-macro_rules! generic_encode_u16_var {
-    ($func_name:ident, $encoding_expr: expr) => {
-        /// # Safety
+    ffi_boundary(|| {
+        assert!(!prefix_key.is_null() && !min_out.is_null() && !max_out.is_null());
+        let prefix = read_key(prefix_key, 128);
+        if significant_bits < 128 && prefix >> significant_bits != 0 {
+            return LindelStatus::KeyOutOfRange;
+        }
+        let cleared_bits = (total_bits - significant_bits) as u32;
+        let (min, span) = match cleared_bits {
+            128 => (0, u128::MAX),
+            _ => (prefix << cleared_bits, (1u128 << cleared_bits) - 1),
+        };
+        write_key(min_out, 128, min);
+        write_key(max_out, 128, min | span);
+        LindelStatus::Ok
+    })
+}
+
+/// Hilbert encode `dims` u64 values of which only the low `bits_per_dim` bits
+/// are meaningful, so for example three 40 bit values fit in a u128.  The key
+/// is for a curve of order `bits_per_dim` and is stored in the smallest
+/// unsigned integer that holds `dims * bits_per_dim` bits.
+///
+/// Values with bits set above `bits_per_dim` are rejected rather than masked.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_encode_u64_bits(
+    values: *const u64,
+    dims: usize,
+    bits_per_dim: u8,
+    result: *mut c_void,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        let key_width = match checked_bits_shape(dims, bits_per_dim) {
+            Ok(key_width) => key_width,
+            Err(status) => return status,
+        };
+        assert!(!values.is_null() && !result.is_null());
+        let mut axes = std::slice::from_raw_parts(values, dims).to_vec();
+        if bits_per_dim < 64 && axes.iter().any(|value| value >> bits_per_dim != 0) {
+            return LindelStatus::ValueOutOfRange;
+        }
+        let key = hilbert::axes_to_index(&mut axes, bits_per_dim as u32);
+        write_key(result, key_width, key);
+        LindelStatus::Ok
+    })
+}
+
+/// Decode a key produced by `hilbert_encode_u64_bits()` with the same number
+/// of dimensions and bits per dimension into `dims` u64 values.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_decode_u64_bits(
+    src: *const c_void,
+    dims: usize,
+    bits_per_dim: u8,
+    dest: *mut u64,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        let key_width = match checked_bits_shape(dims, bits_per_dim) {
+            Ok(key_width) => key_width,
+            Err(status) => return status,
+        };
+        assert!(!src.is_null() && !dest.is_null());
+        let key = read_key(src, key_width);
+        let used_bits = dims * bits_per_dim as usize;
+        if used_bits < 128 && key >> used_bits != 0 {
+            return LindelStatus::KeyOutOfRange;
+        }
+        let axes = std::slice::from_raw_parts_mut(dest, dims);
+        hilbert::index_to_axes(key, bits_per_dim as u32, axes);
+        LindelStatus::Ok
+    })
+}
+
+/// Return the number of leading interleaved bits that two Morton keys of the
+/// same shape have in common, which is the depth of the smallest cell that
+/// contains both of them.  Dividing by `dims` gives the quadtree/octree level.
+///
+/// Returns a negative `LindelStatus` if the shape is invalid.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn morton_common_prefix_bits(
+    key_a: *const c_void,
+    key_b: *const c_void,
+    element_bit_width: u8,
+    dims: usize,
+) -> i32 {
+    ffi_boundary(|| {
+        let common_prefix = || -> Result<i32, LindelStatus> {
+            let a = read_checked_key(element_bit_width, dims, key_a)?;
+            let b = read_checked_key(element_bit_width, dims, key_b)?;
+            let used_bits = element_bit_width as u32 * dims as u32;
+            Ok(match a ^ b {
+                0 => used_bits,
+                different => different.leading_zeros() - (128 - used_bits),
+            } as i32)
+        };
+        common_prefix().unwrap_or_else(i32::from)
+    })
+}
+
+/// Decode a Morton key of `dims` values of `element_bit_width` bits and pack
+/// the values into fixed `field_bits` wide fields of a u128 written to `dest`,
+/// rather than a list.  The first value is stored in the most significant
+/// field and the last in the least significant bits.
+///
+/// Returns `ValueOutOfRange` if a decoded value doesn't fit in `field_bits`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn morton_decode_packed(
+    element_bit_width: u8,
+    dims: usize,
+    src: *const c_void,
+    field_bits: u8,
+    dest: *mut c_void,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        let key = match checked_key(1, element_bit_width, src, dims) {
+            Ok(key) => key,
+            Err(status) => return status,
+        };
+        if !(1..=64).contains(&field_bits) || field_bits as usize * dims > 128 {
+            return LindelStatus::InvalidPrecision;
+        }
+
+        let mut values = [0u128; 1];
+        let values = values.as_mut_ptr() as *mut c_void;
+        let status = decode_key(1, element_bit_width, dims, key, values);
+        if status != LindelStatus::Ok {
+            return status;
+        }
+
+        let mut packed: u128 = 0;
+        for i in 0..dims {
+            let value = read_lane(values, element_bit_width, i);
+            if field_bits < 64 && value >> field_bits != 0 {
+                return LindelStatus::ValueOutOfRange;
+            }
+            packed = packed << field_bits | value as u128;
+        }
+        assert!(!dest.is_null());
+        write_key(dest, 128, packed);
+        LindelStatus::Ok
+    })
+}
+
+/// Spread the low `bits` bits of `value` apart by `dims - 1` zero bits, the
+/// half of Morton encoding that applies to a single dimension, and write the
+/// result to `out` as an unaligned u128.
+///
+/// Bit `i` of the value lands on bit `i * dims` of the result, which is where
+/// the last dimension's bits go.  Shifting the spread value of dimension `d`
+/// left by `dims - 1 - d` and OR-ing the dimensions together builds the same
+/// key as the Morton encoders, where the first dimension is the most
+/// significant bit of each group.
+///
+/// Returns `ValueOutOfRange` if the value doesn't fit in `bits` bits.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn morton_spread(
+    value: u64,
+    dims: u8,
+    bits: u8,
+    out: *mut c_void,
+) -> LindelStatus {
+    if let Err(status) = checked_bits_shape(dims as usize, bits) {
+        return status;
+    }
+    if bits < 64 && value >> bits != 0 {
+        return LindelStatus::ValueOutOfRange;
+    }
+    ffi_boundary(|| {
+        assert!(!out.is_null());
+        let spread = (0..bits as u32)
+            .filter(|&bit| value >> bit & 1 != 0)
+            .fold(0u128, |spread, bit| spread | 1 << (bit * dims as u32));
+        write_key(out, 128, spread);
+        LindelStatus::Ok
+    })
+}
+
+/// Gather every `dims`-th bit of the unaligned u128 at `src` back into the
+/// low `bits` bits of `out`, the inverse of `morton_spread()`.
+///
+/// The bits between them are ignored, so a single dimension of a Morton key
+/// can be extracted by shifting the key right by `dims - 1 - d` first.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn morton_compact(
+    src: *const c_void,
+    dims: u8,
+    bits: u8,
+    out: *mut u64,
+) -> LindelStatus {
+    if let Err(status) = checked_bits_shape(dims as usize, bits) {
+        return status;
+    }
+    ffi_boundary(|| {
+        assert!(!src.is_null() && !out.is_null());
+        let spread = read_key(src, 128);
+        *out = (0..bits as u32)
+            .filter(|&bit| spread >> (bit * dims as u32) & 1 != 0)
+            .fold(0, |value, bit| value | 1 << bit);
+        LindelStatus::Ok
+    })
+}
+
+// The number of bits used by and the width of the key that holds values of
+// the given per-dimension `widths`, for the uneven Morton encoding.
+fn uneven_key_bits(widths: &[u8]) -> Result<(u32, usize), LindelStatus> {
+    if widths.iter().any(|width| !(1..=64).contains(width)) {
+        return Err(LindelStatus::InvalidPrecision);
+    }
+    let used_bits: u32 = widths.iter().map(|&width| width as u32).sum();
+    if used_bits > 128 {
+        return Err(LindelStatus::InvalidDimensions);
+    }
+    Ok((used_bits, (used_bits as usize).next_power_of_two().max(8)))
+}
+
+// Interleave values of the given per-dimension `widths` round-robin from
+// their most significant bits, dropping each dimension once it runs out.
+fn interleave_uneven(values: &[u64], widths: &[u8]) -> u128 {
+    let max_width = *widths.iter().max().unwrap();
+    let mut key: u128 = 0;
+    for round in 0..max_width {
+        for (&value, &width) in values.iter().zip(widths) {
+            if round < width {
+                key = key << 1 | (value >> (width - 1 - round) & 1) as u128;
+            }
+        }
+    }
+    key
+}
+
+// The inverse of interleave_uneven() for a key of `used_bits` bits.
+fn deinterleave_uneven(key: u128, widths: &[u8], used_bits: u32, values: &mut [u64]) {
+    values.iter_mut().for_each(|value| *value = 0);
+    let max_width = *widths.iter().max().unwrap();
+    let mut position = used_bits;
+    for round in 0..max_width {
+        for (value, &width) in values.iter_mut().zip(widths) {
+            if round < width {
+                position -= 1;
+                *value = *value << 1 | (key >> position & 1) as u64;
+            }
+        }
+    }
+}
+
+/// Morton encode `dims` values where each dimension has its own number of
+/// bits given by `widths`, so e.g. X can be finer than Y.
+///
+/// Bits are emitted round-robin starting from the most significant bit of
+/// each value, and a dimension drops out once its bits are exhausted, so with
+/// `widths` of `[20, 12]` the first 24 bits of the key alternate between X and
+/// Y and the last 8 come from the low bits of X.  `values` points to one u64
+/// per dimension and the key is stored in the smallest power of two integer
+/// of at least 8 bits that holds the sum of the widths, at most 128.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn morton_encode_uneven(
+    values: *const u64,
+    widths: *const u8,
+    dims: usize,
+    result: *mut c_void,
+) -> i32 {
+    if dims == 0 {
+        return LindelStatus::InvalidDimensions.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!values.is_null() && !widths.is_null());
+        let values = std::slice::from_raw_parts(values, dims);
+        let widths = std::slice::from_raw_parts(widths, dims);
+        let (_, bits) = match uneven_key_bits(widths) {
+            Ok(shape) => shape,
+            Err(status) => return status.into(),
+        };
+        if values
+            .iter()
+            .zip(widths)
+            .any(|(&value, &width)| width < 64 && value >> width != 0)
+        {
+            return LindelStatus::ValueOutOfRange.into();
+        }
+
+        assert!(!result.is_null());
+        write_key(result, bits, interleave_uneven(values, widths));
+        (bits / 8) as i32
+    })
+}
+
+/// Decode a key produced by `morton_encode_uneven()` with the same `widths`,
+/// writing one u64 per dimension to `dest`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn morton_decode_uneven(
+    widths: *const u8,
+    dims: usize,
+    src: *const c_void,
+    dest: *mut u64,
+) -> LindelStatus {
+    if dims == 0 {
+        return LindelStatus::InvalidDimensions;
+    }
+
+    ffi_boundary(|| {
+        assert!(!widths.is_null());
+        let widths = std::slice::from_raw_parts(widths, dims);
+        let (used_bits, bits) = match uneven_key_bits(widths) {
+            Ok(shape) => shape,
+            Err(status) => return status,
+        };
+        assert!(!src.is_null() && !dest.is_null());
+        let key = read_key(src, bits);
+        if used_bits < 128 && key >> used_bits != 0 {
+            return LindelStatus::KeyOutOfRange;
+        }
+
+        deinterleave_uneven(
+            key,
+            widths,
+            used_bits,
+            std::slice::from_raw_parts_mut(dest, dims),
+        );
+        LindelStatus::Ok
+    })
+}
+
+/// Morton encode dictionary encoded ranks with `morton_encode_uneven()`, using
+/// the fewest bits for each dimension that hold every rank below its
+/// cardinality, `ceil(log2(cardinality))` but at least one.
+///
+/// The chosen width of each dimension is written to `width_out`, which takes
+/// `dims` bytes and is what `morton_decode_uneven()` needs to recover the
+/// ranks.  A cardinality of zero returns `InvalidPrecision` and a rank that
+/// isn't below its cardinality returns `ValueOutOfRange`.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn encode_ranks(
+    ranks: *const u32,
+    cardinalities: *const u32,
+    dims: usize,
+    result: *mut c_void,
+    width_out: *mut u8,
+) -> i32 {
+    if dims == 0 {
+        return LindelStatus::InvalidDimensions.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!ranks.is_null() && !cardinalities.is_null() && !width_out.is_null());
+        let ranks = std::slice::from_raw_parts(ranks, dims);
+        let cardinalities = std::slice::from_raw_parts(cardinalities, dims);
+        if cardinalities.contains(&0) {
+            return LindelStatus::InvalidPrecision.into();
+        }
+        if ranks
+            .iter()
+            .zip(cardinalities)
+            .any(|(rank, cardinality)| rank >= cardinality)
+        {
+            return LindelStatus::ValueOutOfRange.into();
+        }
+
+        let widths: Vec<u8> = cardinalities
+            .iter()
+            .map(|cardinality| (u32::BITS - (cardinality - 1).leading_zeros()).max(1) as u8)
+            .collect();
+        let values: Vec<u64> = ranks.iter().map(|&rank| rank as u64).collect();
+        let written = morton_encode_uneven(values.as_ptr(), widths.as_ptr(), dims, result);
+        if written > 0 {
+            std::ptr::copy_nonoverlapping(widths.as_ptr(), width_out, dims);
+        }
+        written
+    })
+}
+
+// The offset that maps the values of each dimension onto the unsigned range
+// of its width, half the range for signed dimensions selected by
+// `signed_mask` and zero for unsigned ones.
+fn uneven_offsets(widths: &[u8], signed_mask: u32) -> Result<Vec<i128>, LindelStatus> {
+    if widths.len() < 32 && signed_mask >> widths.len() != 0 {
+        return Err(LindelStatus::InvalidDimensions);
+    }
+    Ok(widths
+        .iter()
+        .enumerate()
+        .map(|(i, &width)| {
+            if i < 32 && signed_mask >> i & 1 == 1 {
+                1i128 << (width - 1)
+            } else {
+                0
+            }
+        })
+        .collect())
+}
+
+/// Morton encode `dims` values of per-dimension `widths` like
+/// `morton_encode_uneven()`, where dimension `i` is a two's complement signed
+/// value if bit `i` of `signed_mask` is set and unsigned otherwise.
+///
+/// `values` points to one `LindelHugeint` per dimension, which doesn't need
+/// to be aligned.  Signed values are offset by half their range so negative values
+/// order before positive ones, which only changes the order of the dimensions
+/// that are signed.  A value outside the range of its width and signedness
+/// returns `ValueOutOfRange`.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn morton_encode_uneven_signed(
+    values: *const LindelHugeint,
+    widths: *const u8,
+    signed_mask: u32,
+    dims: usize,
+    result: *mut c_void,
+) -> i32 {
+    if dims == 0 {
+        return LindelStatus::InvalidDimensions.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!values.is_null() && !widths.is_null());
+        let widths = std::slice::from_raw_parts(widths, dims);
+        let (offsets, bits) = match uneven_key_bits(widths).and_then(|(_, bits)| {
+            uneven_offsets(widths, signed_mask).map(|offsets| (offsets, bits))
+        }) {
+            Ok(shape) => shape,
+            Err(status) => return status.into(),
+        };
+
+        let mut lanes = vec![0u64; dims];
+        for (i, lane) in lanes.iter_mut().enumerate() {
+            // Check the range before adding the offset, so values near the
+            // limits of an i128 can't overflow.
+            let value = i128::from(std::ptr::read_unaligned(values.add(i)));
+            let range = -offsets[i]..(1i128 << widths[i]) - offsets[i];
+            match value.checked_add(offsets[i]) {
+                Some(lane_value) if range.contains(&value) => *lane = lane_value as u64,
+                _ => return LindelStatus::ValueOutOfRange.into(),
+            }
+        }
+        assert!(!result.is_null());
+        write_key(result, bits, interleave_uneven(&lanes, widths));
+        (bits / 8) as i32
+    })
+}
+
+/// Decode a key produced by `morton_encode_uneven_signed()` with the same
+/// `widths` and `signed_mask`, writing one `LindelHugeint` per dimension to
+/// `dest`, which doesn't need to be aligned.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn morton_decode_uneven_signed(
+    widths: *const u8,
+    signed_mask: u32,
+    dims: usize,
+    src: *const c_void,
+    dest: *mut LindelHugeint,
+) -> LindelStatus {
+    if dims == 0 {
+        return LindelStatus::InvalidDimensions;
+    }
+
+    ffi_boundary(|| {
+        assert!(!widths.is_null());
+        let widths = std::slice::from_raw_parts(widths, dims);
+        let (used_bits, bits, offsets) =
+            match uneven_key_bits(widths).and_then(|(used_bits, bits)| {
+                uneven_offsets(widths, signed_mask).map(|offsets| (used_bits, bits, offsets))
+            }) {
+                Ok(shape) => shape,
+                Err(status) => return status,
+            };
+        assert!(!src.is_null() && !dest.is_null());
+        let key = read_key(src, bits);
+        if used_bits < 128 && key >> used_bits != 0 {
+            return LindelStatus::KeyOutOfRange;
+        }
+
+        let mut lanes = vec![0u64; dims];
+        deinterleave_uneven(key, widths, used_bits, &mut lanes);
+        for (i, lane) in lanes.into_iter().enumerate() {
+            std::ptr::write_unaligned(dest.add(i), (lane as i128 - offsets[i]).into());
+        }
+        LindelStatus::Ok
+    })
+}
+
+/// Morton encode up to 4 u64 values into a big-endian byte array, for keys
+/// wider than the 128 bits that fit in an integer.
+///
+/// Exactly `dims * 8` bytes are written to `result`, the interleaving is the
+/// same as the other Morton encoders so for one or two dimensions the bytes
+/// are the big-endian bytes of their key.  Because the bytes are big-endian
+/// they sort in the same order as the keys.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.  `BufferTooSmall` is returned if `result_len` is
+/// less than `dims * 8`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn morton_encode_wide(
+    values: *const u64,
+    dims: usize,
+    result: *mut u8,
+    result_len: usize,
+) -> i32 {
+    if !(1..=4).contains(&dims) {
+        return LindelStatus::InvalidDimensions.into();
+    }
+    if result_len < dims * 8 {
+        return LindelStatus::BufferTooSmall.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!values.is_null() && !result.is_null());
+        let values = std::slice::from_raw_parts(values, dims);
+        let bytes = std::slice::from_raw_parts_mut(result, dims * 8);
+        bytes.fill(0);
+        for bit in 0..64 {
+            for (i, value) in values.iter().enumerate() {
+                let position = bit * dims + i;
+                bytes[position / 8] |= ((value >> (63 - bit) & 1) as u8) << (7 - position % 8);
+            }
+        }
+        bytes.len() as i32
+    })
+}
+
+/// Decode a key produced by `morton_encode_wide()` from `src_len` bytes,
+/// which must be `dims * 8`, writing one u64 per dimension to `dest`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn morton_decode_wide(
+    src: *const u8,
+    src_len: usize,
+    dims: usize,
+    dest: *mut u64,
+) -> LindelStatus {
+    if !(1..=4).contains(&dims) || src_len != dims * 8 {
+        return LindelStatus::InvalidDimensions;
+    }
+
+    ffi_boundary(|| {
+        assert!(!src.is_null() && !dest.is_null());
+        let bytes = std::slice::from_raw_parts(src, src_len);
+        let values = std::slice::from_raw_parts_mut(dest, dims);
+        values.fill(0);
+        for bit in 0..64 {
+            for (i, value) in values.iter_mut().enumerate() {
+                let position = bit * dims + i;
+                *value |= u64::from(bytes[position / 8] >> (7 - position % 8) & 1) << (63 - bit);
+            }
+        }
+        LindelStatus::Ok
+    })
+}
+
+/// Describe every supported encode and decode shape as pairs of
+/// `(element_bit_width, max_dims)` bytes, so the SQL layer can validate its
+/// inputs before calling and produce a friendly error.  Every number of
+/// dimensions from 1 to `max_dims` is supported for the element width.
+///
+/// Writes as many whole pairs as fit in `out_cap` bytes of `out`, and returns
+/// the number of bytes that every pair needs, so a null `out` with an
+/// `out_cap` of zero can be used to size the buffer.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_supported_shapes(out: *mut u8, out_cap: usize) -> usize {
+    let shapes = [8u8, 16, 32, 64].map(|width| {
+        let max_dims = (1..=128usize)
+            .take_while(|&dims| key_bits(width, dims).is_some())
+            .count();
+        [width, max_dims as u8]
+    });
+    if !out.is_null() {
+        let pairs = shapes.len().min(out_cap / 2);
+        std::ptr::copy_nonoverlapping(shapes.as_ptr() as *const u8, out, pairs * 2);
+    }
+    shapes.len() * 2
+}
+
+/// Describe which dimension counts could have produced a key, to help debug
+/// a decode that doesn't return the values that were encoded.
+///
+/// `src` points to a key stored in an integer of `src_bits` bits.  Bit `n` of
+/// the result is set if an encode of `n` values of `element_bit_width` bits
+/// produces a key of that width with no bits set beyond the values, for
+/// example a 64 bit key of 16 bit elements returns `(1 << 3) | (1 << 4)`
+/// unless its top 16 bits are set.  The result is zero if no dimension count
+/// fits, including for an invalid encoding type or width.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_describe_key(
+    encoding_type: u8,
+    element_bit_width: u8,
+    src: *const c_void,
+    src_bits: u8,
+) -> u32 {
+    if encoding_type > 1 || src.is_null() || !matches!(src_bits, 8 | 16 | 32 | 64 | 128) {
+        return 0;
+    }
+    let key = read_key(src, src_bits as usize);
+    (1..=16usize)
+        .filter(|&dims| key_bits(element_bit_width, dims) == Some(src_bits as usize))
+        .filter(|&dims| {
+            let used_bits = element_bit_width as usize * dims;
+            used_bits == 128 || key >> used_bits == 0
+        })
+        .fold(0, |mask, dims| mask | 1 << dims)
+}
+
+/// Check that a key stored in an integer of `src_bits_declared` bits has no
+/// bits set above `dims * element_bit_width`, which would mean it was encoded
+/// with a different width or number of dimensions, or is corrupt.
+///
+/// Returns 1 if every set bit falls within the values and 0 if it doesn't, or
+/// a negative `LindelStatus` if the width, number of dimensions or
+/// `src_bits_declared` isn't supported.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_validate_key(
+    src: *const c_void,
+    src_bits_declared: u8,
+    dims: usize,
+    element_bit_width: u8,
+) -> i32 {
+    if let Err(status) = checked_key_bits(element_bit_width, dims) {
+        return status.into();
+    }
+    if !matches!(src_bits_declared, 8 | 16 | 32 | 64 | 128) {
+        return LindelStatus::InvalidPrecision.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!src.is_null());
+        let key = read_key(src, src_bits_declared as usize);
+        let used_bits = element_bit_width as usize * dims;
+        i32::from(used_bits >= 128 || key >> used_bits == 0)
+    })
+}
+
+/// The number of distinct cells on a curve of `dims` dimensions with
+/// `bits_per_dim` bits each, `2^(dims * bits_per_dim)`, written to `out` as an
+/// unaligned u128.  Dividing a key by it normalizes the key to `[0, 1)`.
+///
+/// Returns `InvalidDimensions` for zero dimensions, `InvalidPrecision` for
+/// zero bits per dimension and `ValueOutOfRange` if the count doesn't fit a
+/// u128, which is the case for 128 or more bits in total.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_cell_count(
+    dims: u8,
+    bits_per_dim: u8,
+    out: *mut c_void,
+) -> LindelStatus {
+    if dims == 0 {
+        return LindelStatus::InvalidDimensions;
+    }
+    if bits_per_dim == 0 {
+        return LindelStatus::InvalidPrecision;
+    }
+    let total_bits = dims as u32 * bits_per_dim as u32;
+    if total_bits >= u128::BITS {
+        return LindelStatus::ValueOutOfRange;
+    }
+    ffi_boundary(|| {
+        assert!(!out.is_null());
+        write_key(out, 128, 1 << total_bits);
+        LindelStatus::Ok
+    })
+}
+
+/// Guess the `element_bit_width` that a sample of `n_keys` keys of `dims`
+/// dimensions was encoded with, for columns whose encoding wasn't recorded.
+///
+/// `keys` points to the keys as unaligned u128s.  The highest set bit across
+/// the sample is spread over the dimensions and rounded up to the next of 8,
+/// 16, 32 or 64 bits.  This is only a heuristic, a sample whose values are
+/// all small can't be told apart from a narrower encoding, so the larger the
+/// sample the better.
+///
+/// Returns the element width, or a negative `LindelStatus` if `dims` is zero
+/// or a key has more bits set than any width of `dims` values can produce.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_infer_width(keys: *const c_void, n_keys: usize, dims: u8) -> i32 {
+    if dims == 0 {
+        return LindelStatus::InvalidDimensions.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(n_keys == 0 || !keys.is_null());
+        let high_bits = (0..n_keys)
+            .map(|i| read_key((keys as *const u128).add(i) as *const c_void, 128))
+            .map(|key| u128::BITS - key.leading_zeros())
+            .max()
+            .unwrap_or(0);
+        let bits_per_dim = high_bits.div_ceil(dims as u32);
+        [8u8, 16, 32, 64]
+            .into_iter()
+            .find(|&width| bits_per_dim <= width as u32 && key_bits(width, dims as usize).is_some())
+            .map_or(LindelStatus::KeyOutOfRange.into(), i32::from)
+    })
+}
+
+// Determine if `key` falls within one of the sorted, non-overlapping and
+// inclusive `[start, end]` ranges.
+fn key_in_ranges(key: u64, ranges: &[[u64; 2]]) -> bool {
+    // The index of the first range that starts after the key, so the only
+    // range that could contain the key is the one before it.
+    let after = ranges.partition_point(|range| range[0] <= key);
+    after > 0 && key <= ranges[after - 1][1]
+}
+
+/// Compute the index of the cell `(x, y)` along the classic 2-D Hilbert curve
+/// of side `2^order`, for 2-D map tiling with an explicit order.
+///
+/// This is the well known xy2d() algorithm, its curve starts at `(0, 0)` and
+/// ends at `(2^order - 1, 0)`, which is the same curve as the keys from
+/// `hilbert_encode_u64_bits()` of `[x, y]` with `order` bits per dimension.
+/// `order` must be between 1 and 31 and the coordinates below `2^order`,
+/// otherwise `u64::MAX` is returned which is never a valid index.
+#[no_mangle]
+pub extern "C" fn hilbert_xy2d(order: u8, x: u32, y: u32) -> u64 {
+    let order = order as u32;
+    if !(1..=31).contains(&order) || x >> order != 0 || y >> order != 0 {
+        return u64::MAX;
+    }
+    hilbert::xy_to_d(order, x as u64, y as u64)
+}
+
+/// Compute the cell at index `d` along the classic 2-D Hilbert curve of side
+/// `2^order`, the inverse of `hilbert_xy2d()`.
+///
+/// Returns `InvalidPrecision` if `order` isn't between 1 and 31 and
+/// `ValueOutOfRange` if `d` isn't below `4^order`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_d2xy(
+    order: u8,
+    d: u64,
+    x_out: *mut u32,
+    y_out: *mut u32,
+) -> LindelStatus {
+    let order = order as u32;
+    if !(1..=31).contains(&order) {
+        return LindelStatus::InvalidPrecision;
+    }
+    if d >> (2 * order) != 0 {
+        return LindelStatus::ValueOutOfRange;
+    }
+    ffi_boundary(|| {
+        assert!(!x_out.is_null() && !y_out.is_null());
+        let (x, y) = hilbert::d_to_xy(order, d);
+        *x_out = x as u32;
+        *y_out = y as u32;
+        LindelStatus::Ok
+    })
+}
+
+/// Compute the index of the voxel `(x, y, z)` along the 3-D Hilbert curve of
+/// side `2^order`, for volumetric indexing with an explicit order.
+///
+/// This is John Skilling's algorithm, the same curve as the keys from
+/// `hilbert_encode_u64_bits()` of `[x, y, z]` with `order` bits per
+/// dimension.  The index is written to `result` as an unaligned u128.
+///
+/// Returns `InvalidPrecision` if `order` isn't between 1 and 32 and
+/// `ValueOutOfRange` if a coordinate isn't below `2^order`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_xyz2d(
+    order: u8,
+    x: u32,
+    y: u32,
+    z: u32,
+    result: *mut c_void,
+) -> LindelStatus {
+    let order = order as u32;
+    if !(1..=32).contains(&order) {
+        return LindelStatus::InvalidPrecision;
+    }
+    if [x, y, z].iter().any(|&value| (value as u64) >> order != 0) {
+        return LindelStatus::ValueOutOfRange;
+    }
+    ffi_boundary(|| {
+        assert!(!result.is_null());
+        let mut axes = [x, y, z].map(u64::from);
+        write_key(result, 128, hilbert::axes_to_index(&mut axes, order));
+        LindelStatus::Ok
+    })
+}
+
+/// Compute the voxel at the index that `d` points to along the 3-D Hilbert
+/// curve of side `2^order`, the inverse of `hilbert_xyz2d()`.
+///
+/// Returns `InvalidPrecision` if `order` isn't between 1 and 32 and
+/// `ValueOutOfRange` if the index isn't below `8^order`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_d2xyz(
+    order: u8,
+    d: *const c_void,
+    x_out: *mut u32,
+    y_out: *mut u32,
+    z_out: *mut u32,
+) -> LindelStatus {
+    let order = order as u32;
+    if !(1..=32).contains(&order) {
+        return LindelStatus::InvalidPrecision;
+    }
+    ffi_boundary(|| {
+        assert!(!d.is_null() && !x_out.is_null() && !y_out.is_null() && !z_out.is_null());
+        let d = read_key(d, 128);
+        if d >> (3 * order) != 0 {
+            return LindelStatus::ValueOutOfRange;
+        }
+        let mut axes = [0u64; 3];
+        hilbert::index_to_axes(d, order, &mut axes);
+        *x_out = axes[0] as u32;
+        *y_out = axes[1] as u32;
+        *z_out = axes[2] as u32;
+        LindelStatus::Ok
+    })
+}
+
+/// The minimum number of bits per dimension that represents every coordinate
+/// up to `max_x` and `max_y` without loss, between 1 and 32, e.g. to choose
+/// the `order` of `hilbert_xy2d()` or the element width to encode with.
+#[no_mangle]
+pub extern "C" fn hilbert_min_order_2d(max_x: u32, max_y: u32) -> u8 {
+    (u32::BITS - (max_x | max_y).leading_zeros()).max(1) as u8
+}
+
+/// Determine if a Hilbert key is covered by a set of key ranges.
+///
+/// `ranges` points to `n_ranges` pairs of inclusive `[start, end]` keys which
+/// must be sorted by start and not overlap.  Returns 1 if the key falls within
+/// one of the ranges, 0 if it doesn't, or a negative `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_in_ranges(key: u64, ranges: *const u64, n_ranges: usize) -> i32 {
+    ffi_boundary(|| {
+        if n_ranges == 0 {
+            return 0;
+        }
+        assert!(!ranges.is_null());
+        let ranges = std::slice::from_raw_parts(ranges as *const [u64; 2], n_ranges);
+        i32::from(key_in_ranges(key, ranges))
+    })
+}
+
+/// The most keys that `hilbert_nearby_keys()` returns.
+pub const LINDEL_MAX_NEARBY_KEYS: usize = 1024;
+
+/// Find up to `k` Hilbert keys near the cell that contains a point, to probe
+/// an index for approximate nearest neighbors.
+///
+/// `values` points to `dims` values of `element_bit_width` bits whose key must
+/// fit in a u64.  The point's own key is written to `out` first, followed by
+/// the keys of the cells that share a face with it, then the keys after and
+/// before it along the curve, without duplicates.  At most
+/// `LINDEL_MAX_NEARBY_KEYS` keys are written whatever `k` is.  Returns the number of
+/// keys written, which is zero if the shape is invalid.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_nearby_keys(
+    values: *const c_void,
+    dims: usize,
+    element_bit_width: u8,
+    k: usize,
+    out: *mut u64,
+) -> usize {
+    if values.is_null() || out.is_null() || k == 0 {
+        return 0;
+    }
+    if !matches!(key_bits(element_bit_width, dims), Some(bits) if bits <= 64) {
+        return 0;
+    }
+    let k = k.min(LINDEL_MAX_NEARBY_KEYS);
+
+    ffi_boundary_or(0, || {
+        let bits = element_bit_width as u32;
+        let point: Vec<u64> = (0..dims)
+            .map(|i| read_lane(values, element_bit_width, i))
+            .collect();
+        let key_of = |axes: &[u64]| hilbert::axes_to_index(&mut axes.to_vec(), bits) as u64;
+        let max_value = u64::MAX >> (64 - bits);
+        let max_key = u64::MAX >> (64 - bits * dims as u32);
+
+        // Every cell has its own key, so the point and its face neighbors are
+        // distinct, as are the keys along the curve.  Only a key along the
+        // curve can repeat one of the first few, so those are the only keys
+        // that need checking.
+        let key = key_of(&point);
+        let mut keys = Vec::with_capacity(k);
+        keys.push(key);
+        for i in 0..dims {
+            let below = point[i].checked_sub(1);
+            let above = point[i].checked_add(1).filter(|value| *value <= max_value);
+            for value in below.into_iter().chain(above) {
+                let mut axes = point.clone();
+                axes[i] = value;
+                keys.push(key_of(&axes));
+            }
+        }
+        let face_keys = keys.len();
+        // Returns true once there are `k` keys.
+        let mut add = |candidate: u64| {
+            if keys.len() < k && !keys[..face_keys].contains(&candidate) {
+                keys.push(candidate);
+            }
+            keys.len() >= k
+        };
+
+        let mut distance = 1u64;
+        loop {
+            let after = key.checked_add(distance).filter(|after| *after <= max_key);
+            let before = key.checked_sub(distance);
+            if after.is_none() && before.is_none() {
+                break;
+            }
+            if after.into_iter().chain(before).any(&mut add) {
+                break;
+            }
+            distance += 1;
+        }
+
+        keys.truncate(k);
+        std::ptr::copy_nonoverlapping(keys.as_ptr(), out, keys.len());
+        keys.len()
+    })
+}
+
+/// Test a batch of Morton keys against a set of key ranges, writing 1 to
+/// `out` for each key that falls within one of the ranges and 0 otherwise.
+///
+/// `ranges` points to `n_ranges` pairs of inclusive `[start, end]` keys which
+/// must be sorted by start and not overlap, and `out` must have room for
+/// `n_keys` bytes.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn morton_in_ranges_batch(
+    keys: *const u64,
+    n_keys: usize,
+    ranges: *const u64,
+    n_ranges: usize,
+    out: *mut u8,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        if n_keys == 0 {
+            return LindelStatus::Ok;
+        }
+        assert!(!keys.is_null() && !out.is_null());
+        let keys = std::slice::from_raw_parts(keys, n_keys);
+        let out = std::slice::from_raw_parts_mut(out, n_keys);
+        let ranges: &[[u64; 2]] = if n_ranges == 0 {
+            &[]
+        } else {
+            assert!(!ranges.is_null());
+            std::slice::from_raw_parts(ranges as *const [u64; 2], n_ranges)
+        };
+        for (key, out) in keys.iter().zip(out.iter_mut()) {
+            *out = u8::from(key_in_ranges(*key, ranges));
+        }
+        LindelStatus::Ok
+    })
+}
+
+// Encode the N values in `args` and store the key, which lindel places in the
+// smallest unsigned integer that holds all of the interleaved bits.  Where
+// that integer is wider than the bits, e.g. three u16 values in a u64, the
+// interleaved bits are right aligned so the unused high bits are always zero
+// and don't affect the ordering.
+//
+// When big_endian is set the result is stored in network byte order, so the
+// stored bytes compare in the same order as the keys when sorted byte-wise.
+//
+// Returns the number of bytes written to the result.
+unsafe fn encode<const N: usize, T: Copy, K: lindel::ValidKey<T>>(
+    function: fn([T; N]) -> K,
+    args: &[T],
+    result: *mut c_void,
+    big_endian: bool,
+) -> i32 {
+    let key = function(args.try_into().expect("Invalid length"));
+    *(result as *mut K) = if big_endian { key.to_be() } else { key };
+    std::mem::size_of::<K>() as i32
+}
+
+// Define an exported encoder for one element type, `$dims` lists the number
+// of values that fit in a u128 key, each of which dispatches to a copy of
+// encode() for that number of dimensions.
+macro_rules! generic_encode_var {
+    ($func_name:ident, $encoding_func:path, $type:ty, [$($dims:literal),+]) => {
+        /// Returns the number of bytes written to `result`, or a negative
+        /// `LindelStatus` on error.
+        ///
+        /// # Safety
         ///
         /// This function is unsafe because it dereferences raw pointers.
         #[no_mangle]
         pub unsafe extern "C" fn $func_name(
-            ptr: *const u16,
+            ptr: *const $type,
             len: usize,
             result: *mut c_void,
-        ) -> () {
-            let args = unsafe {
-                assert!(!ptr.is_null());
-                std::slice::from_raw_parts(ptr, len)
-            };
-
-            match args.len() {
-                1 => encode_and_store!($encoding_expr, [args[0]], u16, result), // 16
-                2 => encode_and_store!($encoding_expr, [args[0], args[1]], u32, result), //32
-                3 => encode_and_store!($encoding_expr, [args[0], args[1], args[2]], u64, result), // 48 - 64
-                4 => encode_and_store!(
-                    $encoding_expr,
-                    [args[0], args[1], args[2], args[3]],
-                    u64,
-                    result
-                ), // 64 - 64
-                5 => encode_and_store!(
-                    $encoding_expr,
-                    [args[0], args[1], args[2], args[3], args[4]],
-                    u128,
-                    result
-                ),
-                6 => encode_and_store!(
-                    $encoding_expr,
-                    [args[0], args[1], args[2], args[3], args[4], args[5]],
-                    u128,
-                    result
-                ),
-                7 => encode_and_store!(
-                    $encoding_expr,
-                    [args[0], args[1], args[2], args[3], args[4], args[5], args[6]],
-                    u128,
-                    result
-                ),
-                8 => encode_and_store!(
-                    $encoding_expr,
-                    [args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7]],
-                    u128,
-                    result
-                ),
-                _ => panic!("Invalid length"),
+            big_endian: bool,
+        ) -> i32 {
+            // Reject an empty list and lengths lindel can't encode before a
+            // slice is constructed from a possibly dangling or corrupt length.
+            const MAX_DIMS: usize = 128 / <$type>::BITS as usize;
+            if len == 0 || len > MAX_DIMS {
+                return LindelStatus::InvalidDimensions.into();
             }
+
+            ffi_boundary(|| {
+                let args = unsafe {
+                    assert!(!ptr.is_null());
+                    std::slice::from_raw_parts(ptr, len)
+                };
+
+                match args.len() {
+                    $($dims => encode::<$dims, $type, _>($encoding_func, args, result, big_endian),)+
+                    _ => panic!("Invalid length"),
+                }
+            })
         }
     };
 }
 
-generic_encode_u16_var!(hilbert_encode_u16_var, lindel::hilbert_encode);
-generic_encode_u16_var!(morton_encode_u16_var, lindel::morton_encode);
+generic_encode_var!(
+    hilbert_encode_u8_var,
+    lindel::hilbert_encode,
+    u8,
+    [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]
+);
+generic_encode_var!(
+    morton_encode_u8_var,
+    lindel::morton_encode,
+    u8,
+    [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]
+);
+generic_encode_var!(
+    hilbert_encode_u16_var,
+    lindel::hilbert_encode,
+    u16,
+    [1, 2, 3, 4, 5, 6, 7, 8]
+);
+generic_encode_var!(
+    morton_encode_u16_var,
+    lindel::morton_encode,
+    u16,
+    [1, 2, 3, 4, 5, 6, 7, 8]
+);
+generic_encode_var!(
+    hilbert_encode_u32_var,
+    lindel::hilbert_encode,
+    u32,
+    [1, 2, 3, 4]
+);
+generic_encode_var!(
+    morton_encode_u32_var,
+    lindel::morton_encode,
+    u32,
+    [1, 2, 3, 4]
+);
+generic_encode_var!(hilbert_encode_u64_var, lindel::hilbert_encode, u64, [1, 2]);
+generic_encode_var!(morton_encode_u64_var, lindel::morton_encode, u64, [1, 2]);
 
-macro_rules! generic_encode_u32_var {
-    ($func_name:ident, $encoding_expr: expr) => {
+// Encode and store the result as a 16 byte big-endian value regardless of the
+// width of the key, so keys of any shape can be stored in one BLOB column and
+// still sort correctly when compared byte-wise.
+macro_rules! generic_encode_blob {
+    ($func_name:ident, $encode_func:ident, $element_type:ty, $element_bit_width:expr) => {
+        /// Returns 16, the number of bytes written to `result`, or a negative
+        /// `LindelStatus` on error.
+        ///
         /// # Safety
         ///
         /// This function is unsafe because it dereferences raw pointers.
         #[no_mangle]
         pub unsafe extern "C" fn $func_name(
-            ptr: *const u32,
+            ptr: *const $element_type,
             len: usize,
-            result: *mut c_void,
-        ) -> () {
-            let args = unsafe {
-                assert!(!ptr.is_null());
-                std::slice::from_raw_parts(ptr, len)
-            };
-
-            match args.len() {
-                1 => encode_and_store!($encoding_expr, [args[0]], u32, result),
-                2 => encode_and_store!($encoding_expr, [args[0], args[1]], u64, result),
-                3 => encode_and_store!($encoding_expr, [args[0], args[1], args[2]], u128, result),
-                4 => encode_and_store!(
-                    $encoding_expr,
-                    [args[0], args[1], args[2], args[3]],
-                    u128,
-                    result
-                ),
-                _ => panic!("Invalid length"),
-            }
+            result: *mut u8,
+        ) -> i32 {
+            ffi_boundary(|| {
+                let bits = match key_bits($element_bit_width, len) {
+                    Some(bits) => bits,
+                    None => return LindelStatus::InvalidDimensions.into(),
+                };
+                let mut key: u128 = 0;
+                let written = $encode_func(ptr, len, &mut key as *mut u128 as *mut c_void, false);
+                if written < 0 {
+                    return written;
+                }
+                let key = read_key(&key as *const u128 as *const c_void, bits);
+                assert!(!result.is_null());
+                std::ptr::copy_nonoverlapping(key.to_be_bytes().as_ptr(), result, 16);
+                16
+            })
         }
     };
 }
 
-generic_encode_u32_var!(hilbert_encode_u32_var, lindel::hilbert_encode);
-generic_encode_u32_var!(morton_encode_u32_var, lindel::morton_encode);
+generic_encode_blob!(hilbert_encode_u8_var_blob, hilbert_encode_u8_var, u8, 8);
+generic_encode_blob!(morton_encode_u8_var_blob, morton_encode_u8_var, u8, 8);
+generic_encode_blob!(hilbert_encode_u16_var_blob, hilbert_encode_u16_var, u16, 16);
+generic_encode_blob!(morton_encode_u16_var_blob, morton_encode_u16_var, u16, 16);
+generic_encode_blob!(hilbert_encode_u32_var_blob, hilbert_encode_u32_var, u32, 32);
+generic_encode_blob!(morton_encode_u32_var_blob, morton_encode_u32_var, u32, 32);
+generic_encode_blob!(hilbert_encode_u64_var_blob, hilbert_encode_u64_var, u64, 64);
+generic_encode_blob!(morton_encode_u64_var_blob, morton_encode_u64_var, u64, 64);
+
+/// Flag for the float encoders to reject NaN values rather than encode them.
+pub const LINDEL_REJECT_NAN: u8 = 1;
+/// Flag for the float encoders to reject positive and negative infinity.
+pub const LINDEL_REJECT_INFINITE: u8 = 2;
 
-macro_rules! generic_encode_u64_var {
-    ($func_name:ident, $encoding_expr: expr) => {
+// Encode floating point values from their bits, as the SQL functions do for
+// FLOAT and DOUBLE arrays, after checking them against `float_checks`.
+//
+// NaN values that aren't rejected are all encoded as the bits of the standard
+// quiet NaN regardless of their sign and payload, so every NaN produces the
+// same key.
+macro_rules! generic_encode_float_var {
+    ($func_name:ident, $encode_func:ident, $float_type:ty, $bits_type:ty) => {
+        /// `float_checks` is a combination of `LINDEL_REJECT_NAN` and
+        /// `LINDEL_REJECT_INFINITE`, a rejected value returns `NonFiniteValue`.
+        /// Any other NaN is encoded as the standard quiet NaN.
+        ///
+        /// Returns the number of bytes written to `result`, or a negative
+        /// `LindelStatus` on error.
+        ///
         /// # Safety
         ///
         /// This function is unsafe because it dereferences raw pointers.
         #[no_mangle]
         pub unsafe extern "C" fn $func_name(
-            ptr: *const u64,
+            ptr: *const $float_type,
             len: usize,
             result: *mut c_void,
-        ) -> () {
-            let args = unsafe {
+            big_endian: bool,
+            float_checks: u8,
+        ) -> i32 {
+            const MAX_DIMS: usize = 128 / <$bits_type>::BITS as usize;
+            if len == 0 || len > MAX_DIMS {
+                return LindelStatus::InvalidDimensions.into();
+            }
+
+            ffi_boundary(|| {
                 assert!(!ptr.is_null());
-                std::slice::from_raw_parts(ptr, len)
-            };
+                let values = std::slice::from_raw_parts(ptr, len);
+                let mut bits = [0 as $bits_type; MAX_DIMS];
+                for (bits, value) in bits.iter_mut().zip(values) {
+                    let rejected = if value.is_nan() {
+                        float_checks & LINDEL_REJECT_NAN != 0
+                    } else {
+                        value.is_infinite() && float_checks & LINDEL_REJECT_INFINITE != 0
+                    };
+                    if rejected {
+                        return LindelStatus::NonFiniteValue.into();
+                    }
+                    *bits = if value.is_nan() {
+                        <$float_type>::NAN.to_bits()
+                    } else {
+                        value.to_bits()
+                    };
+                }
+                $encode_func(bits.as_ptr(), len, result, big_endian)
+            })
+        }
+    };
+}
 
-            match args.len() {
-                1 => encode_and_store!($encoding_expr, [args[0]], u64, result),
-                2 => encode_and_store!($encoding_expr, [args[0], args[1]], u128, result),
-                _ => panic!("Invalid length"),
+generic_encode_float_var!(hilbert_encode_f32_var, hilbert_encode_u32_var, f32, u32);
+generic_encode_float_var!(morton_encode_f32_var, morton_encode_u32_var, f32, u32);
+generic_encode_float_var!(hilbert_encode_f64_var, hilbert_encode_u64_var, f64, u64);
+generic_encode_float_var!(morton_encode_f64_var, morton_encode_u64_var, f64, u64);
+
+/// Convert a DuckDB UUID to an unsigned 128 bit dimension value that orders
+/// exactly as DuckDB orders UUIDs, so it can be encoded as one dimension.
+///
+/// DuckDB stores a UUID as a `hugeint_t` whose `upper` half has its top bit
+/// flipped, so that the signed comparison of the hugeint sorts UUIDs by their
+/// text.  `hi` is the bits of `upper` and `lo` is `lower`, and flipping the
+/// bit back gives the 128 bits of the UUID, whose unsigned order is the same.
+/// The value is written to `result` as an unaligned u128.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn encode_uuid_dim(hi: u64, lo: u64, result: *mut c_void) -> LindelStatus {
+    ffi_boundary(|| {
+        assert!(!result.is_null());
+        write_key(result, 128, ((hi ^ 1 << 63) as u128) << 64 | lo as u128);
+        LindelStatus::Ok
+    })
+}
+
+/// Convert a dimension value produced by `encode_uuid_dim()` back into the
+/// `upper` and `lower` halves of DuckDB's `hugeint_t` representation of the
+/// UUID.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn decode_uuid_dim(
+    src: *const c_void,
+    hi_out: *mut u64,
+    lo_out: *mut u64,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        assert!(!src.is_null() && !hi_out.is_null() && !lo_out.is_null());
+        let value = read_key(src, 128);
+        *hi_out = (value >> 64) as u64 ^ 1 << 63;
+        *lo_out = value as u64;
+        LindelStatus::Ok
+    })
+}
+
+// The largest scale of a DuckDB DECIMAL.
+const MAX_DECIMAL_SCALE: u8 = 38;
+
+/// Hilbert encode up to two DECIMAL values from their underlying scaled
+/// integers, so they are ordered numerically without converting to floats.
+///
+/// `values` points to one `LindelHugeint` per dimension, which doesn't need to
+/// be aligned, and each value is mapped to an unsigned integer by flipping its
+/// sign bit so negative values come before positive ones.  A single value
+/// uses the full 128 bits, so every DECIMAL(38) fits.  Two values share the
+/// 128 bit key, so each must fit in 64 bits as it does for a DECIMAL of up to
+/// 18 digits, and `ValueOutOfRange` is returned for wider values.  The key is
+/// always 16 bytes.
+///
+/// `scale` is only metadata, the values of every dimension must share it for
+/// the keys to be comparable, and it returns `InvalidPrecision` if it's
+/// beyond the 38 digits of a DECIMAL.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_encode_decimal_var(
+    values: *const LindelHugeint,
+    scale: u8,
+    dims: usize,
+    result: *mut c_void,
+) -> i32 {
+    if scale > MAX_DECIMAL_SCALE {
+        return LindelStatus::InvalidPrecision.into();
+    }
+    if !(1..=2).contains(&dims) {
+        return LindelStatus::InvalidDimensions.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!values.is_null() && !result.is_null());
+        let value = |i| i128::from(std::ptr::read_unaligned(values.add(i)));
+        let key = if dims == 1 {
+            lindel::hilbert_encode([value(0) as u128 ^ 1 << 127])
+        } else {
+            let mut bits = [0u64; 2];
+            for (i, bits) in bits.iter_mut().enumerate() {
+                *bits = match i64::try_from(value(i)) {
+                    Ok(value) => value as u64 ^ 1 << 63,
+                    Err(_) => return LindelStatus::ValueOutOfRange.into(),
+                };
             }
+            lindel::hilbert_encode(bits)
+        };
+        write_key(result, 128, key);
+        16
+    })
+}
+
+/// Decode a key produced by `hilbert_encode_decimal_var()` back into the
+/// scaled integers of `dims` DECIMAL values, written to `dest` which doesn't
+/// need to be aligned.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_decode_decimal(
+    src: *const c_void,
+    scale: u8,
+    dims: usize,
+    dest: *mut LindelHugeint,
+) -> LindelStatus {
+    if scale > MAX_DECIMAL_SCALE {
+        return LindelStatus::InvalidPrecision;
+    }
+    if !(1..=2).contains(&dims) {
+        return LindelStatus::InvalidDimensions;
+    }
+
+    ffi_boundary(|| {
+        assert!(!src.is_null() && !dest.is_null());
+        let key = read_key(src, 128);
+        let values: Vec<i128> = if dims == 1 {
+            let [bits]: [u128; 1] = lindel::hilbert_decode(key);
+            vec![(bits ^ 1 << 127) as i128]
+        } else {
+            let bits: [u64; 2] = lindel::hilbert_decode(key);
+            bits.iter()
+                .map(|bits| (bits ^ 1 << 63) as i64 as i128)
+                .collect()
+        };
+        for (i, value) in values.into_iter().enumerate() {
+            std::ptr::write_unaligned(dest.add(i), value.into());
         }
-    };
+        LindelStatus::Ok
+    })
 }
 
-generic_encode_u64_var!(hilbert_encode_u64_var, lindel::hilbert_encode);
-generic_encode_u64_var!(morton_encode_u64_var, lindel::morton_encode);
+// Encode `dims` values with the exported encoder for the encoding type and
+// element width, returning the number of bytes written or a negative status.
+unsafe fn encode_values(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const c_void,
+    dims: usize,
+    result: *mut c_void,
+) -> i32 {
+    match (encoding_type, element_bit_width) {
+        (0, 8) => hilbert_encode_u8_var(values as *const u8, dims, result, false),
+        (0, 16) => hilbert_encode_u16_var(values as *const u16, dims, result, false),
+        (0, 32) => hilbert_encode_u32_var(values as *const u32, dims, result, false),
+        (0, 64) => hilbert_encode_u64_var(values as *const u64, dims, result, false),
+        (1, 8) => morton_encode_u8_var(values as *const u8, dims, result, false),
+        (1, 16) => morton_encode_u16_var(values as *const u16, dims, result, false),
+        (1, 32) => morton_encode_u32_var(values as *const u32, dims, result, false),
+        (1, 64) => morton_encode_u64_var(values as *const u64, dims, result, false),
+        (0 | 1, _) => LindelStatus::InvalidElementBitWidth.into(),
+        _ => LindelStatus::InvalidEncodingType.into(),
+    }
+}
 
-#[cfg(test)]
-mod tests {}
+/// Encode `dims` values of `element_bit_width` bits, a single entry point for
+/// every element width rather than picking one of the typed encoders.
+///
+/// `values` points to the raw bytes of the values in native byte order.
+/// Neither `values` nor `result` needs to be aligned.
+///
+/// The key is always stored in the smallest of 8, 16, 32, 64 or 128 bits that
+/// holds all `dims * element_bit_width` bits, so no bits are ever lost, and
+/// `InvalidDimensions` is returned if they need more than 128 bits, for which
+/// `morton_encode_wide()` can be used instead.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn encode_any(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const u8,
+    dims: usize,
+    result: *mut c_void,
+) -> i32 {
+    if encoding_type > 1 {
+        return LindelStatus::InvalidEncodingType.into();
+    }
+    if let Err(status) = checked_key_bits(element_bit_width, dims) {
+        return status.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!values.is_null() && !result.is_null());
+        // The typed encoders need aligned values and store the key aligned,
+        // so go through scratch space on both sides.
+        let mut scratch = [0u128; 1];
+        let scratch = scratch.as_mut_ptr() as *mut c_void;
+        std::ptr::copy_nonoverlapping(
+            values,
+            scratch as *mut u8,
+            dims * element_bit_width as usize / 8,
+        );
+
+        let mut key = [0u128; 1];
+        let written = encode_values(
+            encoding_type,
+            element_bit_width,
+            scratch,
+            dims,
+            key.as_mut_ptr() as *mut c_void,
+        );
+        if written > 0 {
+            write_key(result, written as usize * 8, key[0]);
+        }
+        written
+    })
+}
+
+/// Encode like `encode_any()` but return the key as its high and low 64 bits
+/// through `hi` and `lo`, so a binding can assemble a 128 bit key without
+/// passing a u128 across the FFI boundary.  Keys narrower than 128 bits are
+/// zero extended, so `hi` is zero for them.
+///
+/// Returns the number of bytes in the key, or a negative `LindelStatus` on
+/// error, in which case neither `hi` nor `lo` is written.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn encode_hi_lo(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const u8,
+    dims: usize,
+    hi: *mut u64,
+    lo: *mut u64,
+) -> i32 {
+    let mut key = [0u128; 1];
+    let key_ptr = key.as_mut_ptr() as *mut c_void;
+    let written = encode_any(encoding_type, element_bit_width, values, dims, key_ptr);
+    if written < 0 {
+        return written;
+    }
+
+    ffi_boundary(|| {
+        assert!(!hi.is_null() && !lo.is_null());
+        let key = read_key(key_ptr, written as usize * 8);
+        std::ptr::write_unaligned(hi, (key >> 64) as u64);
+        std::ptr::write_unaligned(lo, key as u64);
+        written
+    })
+}
+
+/// Encode like `encode_any()` into row `row` of a packed buffer of 16 byte
+/// keys, the layout of an Arrow `FixedSizeBinary(16)` array, so a batch can be
+/// exported without a copy or an allocation per row.
+///
+/// The key is widened to 128 bits and stored big-endian at byte
+/// `row * 16` of `out`, so the bytes of the keys sort in the same order as the
+/// keys themselves.  `out_len` is the size of the whole buffer in bytes.
+///
+/// Returns the number of bytes written, always 16, or a negative
+/// `LindelStatus` on error.  `BufferTooSmall` is returned if the row doesn't
+/// fit within `out_len` bytes.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn encode_fixed_binary(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const u8,
+    dims: usize,
+    row: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> i32 {
+    match row.checked_add(1).and_then(|rows| rows.checked_mul(16)) {
+        Some(end) if end <= out_len => {}
+        _ => return LindelStatus::BufferTooSmall.into(),
+    }
+    let mut key = [0u128; 1];
+    let key_ptr = key.as_mut_ptr() as *mut c_void;
+    let written = encode_any(encoding_type, element_bit_width, values, dims, key_ptr);
+    if written < 0 {
+        return written;
+    }
+
+    ffi_boundary(|| {
+        assert!(!out.is_null());
+        let key = read_key(key_ptr, written as usize * 8);
+        let bytes = key.to_be_bytes();
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), out.add(row * 16), bytes.len());
+        bytes.len() as i32
+    })
+}
+
+// Hilbert encode `row_count` rows of N u32 columns, storing each key
+// unaligned one after another.
+//
+// Returns the number of bytes written for each key.
+unsafe fn encode_columns<const N: usize, K: lindel::ValidKey<u32>>(
+    hilbert: fn([u32; N]) -> K,
+    columns: &[*const u32],
+    row_count: usize,
+    result: *mut c_void,
+) -> i32 {
+    let columns: [*const u32; N] = columns.try_into().expect("Invalid length");
+    let result = result as *mut K;
+    for i in 0..row_count {
+        let row = columns.map(|column| *column.add(i));
+        std::ptr::write_unaligned(result.add(i), hilbert(row));
+    }
+    std::mem::size_of::<K>() as i32
+}
+
+/// Hilbert encode `row_count` rows of `dims` u32 values stored column by
+/// column, as DuckDB vectors are, so a whole chunk can be encoded without
+/// transposing it into rows first.
+///
+/// Value `j` of row `i` is read from `dim_ptrs[j][i]`.  The keys are written
+/// one after another to `result` in native byte order, each of the size that
+/// `hilbert_encode_u32_var()` writes for `dims` values, and `result` doesn't
+/// need to be aligned.  The shape is validated once for the whole chunk.
+///
+/// Returns the number of bytes written for each key, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_encode_soa(
+    dim_ptrs: *const *const u32,
+    dims: usize,
+    row_count: usize,
+    result: *mut c_void,
+) -> i32 {
+    if let Err(status) = checked_key_bits(32, dims) {
+        return status.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!dim_ptrs.is_null() && !result.is_null());
+        let columns = std::slice::from_raw_parts(dim_ptrs, dims);
+        assert!(columns.iter().all(|column| !column.is_null()));
+        match dims {
+            1 => encode_columns(lindel::hilbert_encode::<u32, 1>, columns, row_count, result),
+            2 => encode_columns(lindel::hilbert_encode::<u32, 2>, columns, row_count, result),
+            3 => encode_columns(lindel::hilbert_encode::<u32, 3>, columns, row_count, result),
+            4 => encode_columns(lindel::hilbert_encode::<u32, 4>, columns, row_count, result),
+            _ => panic!("Invalid length"),
+        }
+    })
+}
+
+/// Byte order for `encode_from_bytes()` of values stored little endian.
+pub const LINDEL_LITTLE_ENDIAN: u8 = 0;
+/// Byte order for `encode_from_bytes()` of values stored big endian.
+pub const LINDEL_BIG_ENDIAN: u8 = 1;
+
+/// Encode `dims` values of `element_bit_width` bits read from raw bytes in
+/// the given `byte_order`, either `LINDEL_LITTLE_ENDIAN` or
+/// `LINDEL_BIG_ENDIAN`, e.g. coordinates from a BLOB in an external format.
+///
+/// Neither `src` nor `result` needs to be aligned, and the key is stored in
+/// native byte order like the other encoders.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn encode_from_bytes(
+    encoding_type: u8,
+    element_bit_width: u8,
+    dims: usize,
+    byte_order: u8,
+    src: *const u8,
+    result: *mut c_void,
+) -> i32 {
+    let swap = match byte_order {
+        LINDEL_LITTLE_ENDIAN => cfg!(target_endian = "big"),
+        LINDEL_BIG_ENDIAN => cfg!(target_endian = "little"),
+        _ => return LindelStatus::InvalidByteOrder.into(),
+    };
+    if let Err(status) = checked_key_bits(element_bit_width, dims) {
+        return status.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!src.is_null());
+        let mut scratch = [0u128; 1];
+        let scratch = scratch.as_mut_ptr() as *mut c_void;
+        for i in 0..dims {
+            let value = read_lane(src as *const c_void, element_bit_width, i);
+            let value = if swap {
+                value.swap_bytes() >> (64 - element_bit_width as u32)
+            } else {
+                value
+            };
+            write_lane(scratch, element_bit_width, i, value);
+        }
+        encode_any(
+            encoding_type,
+            element_bit_width,
+            scratch as *const u8,
+            dims,
+            result,
+        )
+    })
+}
+
+/// Compute the distance along the Hilbert curve between two points of `dims`
+/// values of `element_bit_width` bits, the absolute difference of their
+/// indices, which avoids overflowing a signed subtraction of 128 bit keys.
+///
+/// The distance is written to `result` as an unaligned u128.
+///
+/// Returns `Ok` as zero, or a negative `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_distance(
+    values_a: *const c_void,
+    values_b: *const c_void,
+    dims: usize,
+    element_bit_width: u8,
+    result: *mut c_void,
+) -> i32 {
+    if let Err(status) = checked_key_bits(element_bit_width, dims) {
+        return status.into();
+    }
+
+    ffi_boundary(|| {
+        let index = |values: *const c_void| {
+            let mut key = [0u128; 1];
+            let written = encode_any(
+                0,
+                element_bit_width,
+                values as *const u8,
+                dims,
+                key.as_mut_ptr() as *mut c_void,
+            );
+            match written {
+                written if written > 0 => Ok(read_key(
+                    key.as_ptr() as *const c_void,
+                    written as usize * 8,
+                )),
+                status => Err(status),
+            }
+        };
+        let distance = match (index(values_a), index(values_b)) {
+            (Ok(a), Ok(b)) => a.abs_diff(b),
+            (Err(status), _) | (_, Err(status)) => return status,
+        };
+        assert!(!result.is_null());
+        write_key(result, 128, distance);
+        LindelStatus::Ok.into()
+    })
+}
+
+/// Encode `dims` values of `element_bit_width` bits into a destination that
+/// can hold `result_cap` bytes.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.  If the key is wider than `result_cap` bytes
+/// nothing is written and `BufferTooSmall` is returned.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_encode_checked(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const c_void,
+    dims: usize,
+    result: *mut c_void,
+    result_cap: usize,
+) -> i32 {
+    let bits = match checked_key_bits(element_bit_width, dims) {
+        Ok(bits) => bits,
+        Err(status) => return status.into(),
+    };
+    if bits / 8 > result_cap {
+        return LindelStatus::BufferTooSmall.into();
+    }
+    encode_any(
+        encoding_type,
+        element_bit_width,
+        values as *const u8,
+        dims,
+        result,
+    )
+}
+
+// Reverse the order of the bits of each of the `dims` lanes selected by
+// `reverse_mask`, which must only select existing dimensions.
+unsafe fn reverse_lanes(
+    values: *mut c_void,
+    element_bit_width: u8,
+    dims: usize,
+    reverse_mask: u32,
+) -> Result<(), LindelStatus> {
+    if dims < 32 && reverse_mask >> dims != 0 {
+        return Err(LindelStatus::InvalidDimensions);
+    }
+    for i in (0..dims).filter(|i| reverse_mask & (1 << i) != 0) {
+        let value = read_lane(values, element_bit_width, i);
+        let reversed = value.reverse_bits() >> (64 - element_bit_width as u32);
+        write_lane(values, element_bit_width, i, reversed);
+    }
+    Ok(())
+}
+
+/// Encode `dims` values of `element_bit_width` bits after reversing the order
+/// of the bits of each dimension `i` that has bit `i` of `reverse_mask` set.
+///
+/// Reversing a dimension makes its least significant bits dominate locality
+/// along that axis instead of its most significant bits.  A `reverse_mask` of
+/// zero encodes exactly like the encoder for the element width.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_encode_bit_reversed(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const c_void,
+    dims: usize,
+    reverse_mask: u32,
+    result: *mut c_void,
+) -> i32 {
+    if let Err(status) = checked_key_bits(element_bit_width, dims) {
+        return status.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!values.is_null() && !result.is_null());
+        let mut scratch = [0u128; 1];
+        let scratch = scratch.as_mut_ptr() as *mut c_void;
+        std::ptr::copy_nonoverlapping(
+            values as *const u8,
+            scratch as *mut u8,
+            dims * element_bit_width as usize / 8,
+        );
+        match reverse_lanes(scratch, element_bit_width, dims, reverse_mask) {
+            Ok(()) => encode_any(
+                encoding_type,
+                element_bit_width,
+                scratch as *const u8,
+                dims,
+                result,
+            ),
+            Err(status) => status.into(),
+        }
+    })
+}
+
+/// Decode a key produced by `lindel_encode_bit_reversed()` with the same
+/// `reverse_mask`, restoring the order of the bits of the reversed dimensions.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_bit_reversed(
+    encoding_type: u8,
+    element_bit_width: u8,
+    reverse_mask: u32,
+    src: *const c_void,
+    dest: *mut c_void,
+    dest_len: usize,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        let key = match checked_key(encoding_type, element_bit_width, src, dest_len) {
+            Ok(key) => key,
+            Err(status) => return status,
+        };
+        let status = decode_key(encoding_type, element_bit_width, dest_len, key, dest);
+        if status != LindelStatus::Ok {
+            return status;
+        }
+        match reverse_lanes(dest, element_bit_width, dest_len, reverse_mask) {
+            Ok(()) => LindelStatus::Ok,
+            Err(status) => status,
+        }
+    })
+}
+
+/// Encode `dims` values of `element_bit_width` bits snapped to a grid of
+/// cells `2^resolution_bits` wide, by shifting each value right by
+/// `resolution_bits` before interleaving, for level of detail indexing.
+///
+/// Every point within the same cell produces the same key, and a
+/// `resolution_bits` of zero encodes exactly like the encoder for the element
+/// width.  `resolution_bits` must be less than `element_bit_width`, otherwise
+/// `InvalidPrecision` is returned.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_encode_resolution(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const c_void,
+    dims: usize,
+    resolution_bits: u8,
+    result: *mut c_void,
+) -> i32 {
+    if let Err(status) = checked_key_bits(element_bit_width, dims) {
+        return status.into();
+    }
+    if resolution_bits >= element_bit_width {
+        return LindelStatus::InvalidPrecision.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!values.is_null());
+        let mut scratch = [0u128; 1];
+        let scratch = scratch.as_mut_ptr() as *mut c_void;
+        for i in 0..dims {
+            let value = read_lane(values, element_bit_width, i);
+            write_lane(scratch, element_bit_width, i, value >> resolution_bits);
+        }
+        encode_any(
+            encoding_type,
+            element_bit_width,
+            scratch as *const u8,
+            dims,
+            result,
+        )
+    })
+}
+
+/// Decode a key produced by `lindel_encode_resolution()` with the same
+/// `resolution_bits`, returning the lower corner of its cell by shifting each
+/// value back left.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_resolution(
+    encoding_type: u8,
+    element_bit_width: u8,
+    resolution_bits: u8,
+    src: *const c_void,
+    dest: *mut c_void,
+    dest_len: usize,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        let key = match checked_key(encoding_type, element_bit_width, src, dest_len) {
+            Ok(key) => key,
+            Err(status) => return status,
+        };
+        if resolution_bits >= element_bit_width {
+            return LindelStatus::InvalidPrecision;
+        }
+        let status = decode_key(encoding_type, element_bit_width, dest_len, key, dest);
+        if status != LindelStatus::Ok {
+            return status;
+        }
+        // The snapped values only use the low bits, anything above them
+        // can't have come from an encode at this resolution.
+        let value_bits = element_bit_width - resolution_bits;
+        if (0..dest_len).any(|i| read_lane(dest, element_bit_width, i) >> value_bits != 0) {
+            return LindelStatus::KeyOutOfRange;
+        }
+        for i in 0..dest_len {
+            let value = read_lane(dest, element_bit_width, i);
+            write_lane(dest, element_bit_width, i, value << resolution_bits);
+        }
+        LindelStatus::Ok
+    })
+}
+
+/// Encode `dims` values of `element_bit_width` bits into a DuckDB HUGEINT,
+/// a signed i128, that orders the same as the unsigned key.
+///
+/// The key is widened to 128 bits and its top bit flipped, so keys at or
+/// above `2^127`, which would be negative as a plain i128, still sort after
+/// the keys below it.  Every key is flipped whatever its width, so keys of
+/// the same shape always compare correctly.  `result` receives 16 bytes and
+/// doesn't need to be aligned.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_encode_hugeint(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const c_void,
+    dims: usize,
+    result: *mut c_void,
+) -> i32 {
+    if let Err(status) = checked_key_bits(element_bit_width, dims) {
+        return status.into();
+    }
+
+    ffi_boundary(|| {
+        let mut key = [0u128; 1];
+        let key_ptr = key.as_mut_ptr() as *mut c_void;
+        let written = encode_any(
+            encoding_type,
+            element_bit_width,
+            values as *const u8,
+            dims,
+            key_ptr,
+        );
+        if written < 0 {
+            return written;
+        }
+        assert!(!result.is_null());
+        let key = read_key(key_ptr, written as usize * 8);
+        write_key(result, 128, key ^ 1 << 127);
+        16
+    })
+}
+
+/// Decode a HUGEINT produced by `lindel_encode_hugeint()`, undoing the flip of
+/// its top bit before decoding `dest_len` values of `element_bit_width` bits.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_hugeint(
+    encoding_type: u8,
+    element_bit_width: u8,
+    src: *const c_void,
+    dest: *mut c_void,
+    dest_len: usize,
+) -> LindelStatus {
+    if encoding_type > 1 {
+        return LindelStatus::InvalidEncodingType;
+    }
+    if let Err(status) = checked_key_bits(element_bit_width, dest_len) {
+        return status;
+    }
+
+    ffi_boundary(|| {
+        assert!(!src.is_null());
+        let key = read_key(src, 128) ^ 1 << 127;
+        let used_bits = element_bit_width as usize * dest_len;
+        if used_bits < 128 && key >> used_bits != 0 {
+            return LindelStatus::KeyOutOfRange;
+        }
+        decode_key(encoding_type, element_bit_width, dest_len, key, dest)
+    })
+}
+
+/// Encode `dims` signed values relative to a per-dimension origin, so
+/// dimensions centered on different points share one curve.
+///
+/// Each value has its offset from `offsets` subtracted and is then stored as a
+/// signed integer of `element_bit_width` bits with its sign bit flipped, so
+/// `offset - 2^(w-1)` encodes as zero and the keys order like the values.
+/// Both `values` and `offsets` are `dims` i64s.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error, `ValueOutOfRange` if a value isn't within
+/// `[offset - 2^(w-1), offset + 2^(w-1) - 1]`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_encode_offset(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const i64,
+    offsets: *const i64,
+    dims: usize,
+    result: *mut c_void,
+) -> i32 {
+    if let Err(status) = checked_key_bits(element_bit_width, dims) {
+        return status.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!values.is_null() && !offsets.is_null());
+        let half = 1i128 << (element_bit_width - 1);
+        let mut lanes = [0u128; 1];
+        let lanes_ptr = lanes.as_mut_ptr() as *mut c_void;
+        for i in 0..dims {
+            let value = std::ptr::read_unaligned(values.add(i)) as i128;
+            let shifted = value - std::ptr::read_unaligned(offsets.add(i)) as i128;
+            if !(-half..half).contains(&shifted) {
+                return LindelStatus::ValueOutOfRange.into();
+            }
+            write_lane(lanes_ptr, element_bit_width, i, (shifted + half) as u64);
+        }
+        encode_any(
+            encoding_type,
+            element_bit_width,
+            lanes_ptr as *const u8,
+            dims,
+            result,
+        )
+    })
+}
+
+/// Decode a key produced by `lindel_encode_offset()` with the same `offsets`,
+/// writing `dest_len` i64 values to `dest`.
+///
+/// Returns `ValueOutOfRange` if adding an offset back leaves the range of an
+/// i64, in which case `dest` isn't written.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_offset(
+    encoding_type: u8,
+    element_bit_width: u8,
+    offsets: *const i64,
+    src: *const c_void,
+    dest: *mut i64,
+    dest_len: usize,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        let key = match checked_key(encoding_type, element_bit_width, src, dest_len) {
+            Ok(key) => key,
+            Err(status) => return status,
+        };
+        let mut lanes = [0u128; 1];
+        let lanes_ptr = lanes.as_mut_ptr() as *mut c_void;
+        let status = decode_key(encoding_type, element_bit_width, dest_len, key, lanes_ptr);
+        if status != LindelStatus::Ok {
+            return status;
+        }
+
+        assert!(!offsets.is_null() && !dest.is_null());
+        let half = 1i128 << (element_bit_width - 1);
+        let mut values = Vec::with_capacity(dest_len);
+        for i in 0..dest_len {
+            let shifted = read_lane(lanes_ptr, element_bit_width, i) as i128 - half;
+            let offset = std::ptr::read_unaligned(offsets.add(i)) as i128;
+            match i64::try_from(shifted + offset) {
+                Ok(value) => values.push(value),
+                Err(_) => return LindelStatus::ValueOutOfRange,
+            }
+        }
+        for (i, value) in values.into_iter().enumerate() {
+            std::ptr::write_unaligned(dest.add(i), value);
+        }
+        LindelStatus::Ok
+    })
+}
+
+// Reverse the order of the first `dims` lanes in place.
+unsafe fn reverse_dims_in_place(values: *mut c_void, element_bit_width: u8, dims: usize) {
+    for i in 0..dims / 2 {
+        let first = read_lane(values, element_bit_width, i);
+        let last = read_lane(values, element_bit_width, dims - 1 - i);
+        write_lane(values, element_bit_width, i, last);
+        write_lane(values, element_bit_width, dims - 1 - i, first);
+    }
+}
+
+/// Encode `dims` values of `element_bit_width` bits, taking them in reverse
+/// order when `reverse_dims` is set, e.g. so a `[y, x]` list produces the same
+/// key as the `[x, y]` list from another source.
+///
+/// With `reverse_dims` clear this encodes exactly like the encoder for the
+/// element width.  `values` doesn't need to be aligned.
+///
+/// This reorders whole dimensions, `lindel_encode_bit_reversed()` reverses the
+/// bits within them instead.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_encode_dim_order(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const c_void,
+    dims: usize,
+    reverse_dims: bool,
+    result: *mut c_void,
+) -> i32 {
+    if let Err(status) = checked_key_bits(element_bit_width, dims) {
+        return status.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!values.is_null());
+        let mut scratch = [0u128; 1];
+        let scratch = scratch.as_mut_ptr() as *mut c_void;
+        std::ptr::copy_nonoverlapping(
+            values as *const u8,
+            scratch as *mut u8,
+            dims * element_bit_width as usize / 8,
+        );
+        if reverse_dims {
+            reverse_dims_in_place(scratch, element_bit_width, dims);
+        }
+        encode_any(
+            encoding_type,
+            element_bit_width,
+            scratch as *const u8,
+            dims,
+            result,
+        )
+    })
+}
+
+/// Decode a key produced by `lindel_encode_dim_order()` with the same
+/// `reverse_dims`, writing the values back in the order they were given.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_dim_order(
+    encoding_type: u8,
+    element_bit_width: u8,
+    reverse_dims: bool,
+    src: *const c_void,
+    dest: *mut c_void,
+    dest_len: usize,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        let key = match checked_key(encoding_type, element_bit_width, src, dest_len) {
+            Ok(key) => key,
+            Err(status) => return status,
+        };
+        let status = decode_key(encoding_type, element_bit_width, dest_len, key, dest);
+        if status == LindelStatus::Ok && reverse_dims {
+            reverse_dims_in_place(dest, element_bit_width, dest_len);
+        }
+        status
+    })
+}
+
+// Invert each of the `dims` matrices of `element_bit_width` rows that
+// `matrices` points to, returning None if any of them is singular.
+unsafe fn invert_matrices(
+    matrices: *const u64,
+    element_bit_width: u8,
+    dims: usize,
+) -> Option<Vec<Vec<u64>>> {
+    assert!(!matrices.is_null());
+    let rows = std::slice::from_raw_parts(matrices, dims * element_bit_width as usize);
+    rows.chunks(element_bit_width as usize)
+        .map(gf2::invert)
+        .collect()
+}
+
+/// Encode `dims` values of `element_bit_width` bits after transforming the
+/// bits of each value by an invertible matrix over GF(2), which generalizes
+/// bit reversal and Gray coding into any linear reshuffling of the bits.
+///
+/// `matrices` points to `dims` matrices of `element_bit_width` rows each, one
+/// per dimension.  Bit `i` of a transformed value is the parity of the bits
+/// of the value selected by row `i`, so the identity matrix has row `i` equal
+/// to `1 << i`.  A singular matrix is rejected with `SingularMatrix`.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_encode_transformed(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const c_void,
+    dims: usize,
+    matrices: *const u64,
+    result: *mut c_void,
+) -> i32 {
+    if let Err(status) = checked_key_bits(element_bit_width, dims) {
+        return status.into();
+    }
+
+    ffi_boundary(|| {
+        assert!(!values.is_null());
+        if invert_matrices(matrices, element_bit_width, dims).is_none() {
+            return LindelStatus::SingularMatrix.into();
+        }
+        let rows = std::slice::from_raw_parts(matrices, dims * element_bit_width as usize);
+        let mut scratch = [0u128; 1];
+        let scratch = scratch.as_mut_ptr() as *mut c_void;
+        for (i, matrix) in rows.chunks(element_bit_width as usize).enumerate() {
+            let value = read_lane(values, element_bit_width, i);
+            write_lane(scratch, element_bit_width, i, gf2::apply(matrix, value));
+        }
+        encode_any(
+            encoding_type,
+            element_bit_width,
+            scratch as *const u8,
+            dims,
+            result,
+        )
+    })
+}
+
+/// Decode a key produced by `lindel_encode_transformed()` with the same
+/// matrices, applying their inverses to restore the original values.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn perform_decode_transformed(
+    encoding_type: u8,
+    element_bit_width: u8,
+    matrices: *const u64,
+    src: *const c_void,
+    dest: *mut c_void,
+    dest_len: usize,
+) -> LindelStatus {
+    ffi_boundary(|| {
+        let key = match checked_key(encoding_type, element_bit_width, src, dest_len) {
+            Ok(key) => key,
+            Err(status) => return status,
+        };
+        let inverses = match invert_matrices(matrices, element_bit_width, dest_len) {
+            Some(inverses) => inverses,
+            None => return LindelStatus::SingularMatrix,
+        };
+        let status = decode_key(encoding_type, element_bit_width, dest_len, key, dest);
+        if status != LindelStatus::Ok {
+            return status;
+        }
+        for (i, inverse) in inverses.iter().enumerate() {
+            let value = read_lane(dest, element_bit_width, i);
+            write_lane(dest, element_bit_width, i, gf2::apply(inverse, value));
+        }
+        LindelStatus::Ok
+    })
+}
+
+/// Re-encode a key of `dims` values of `element_bit_width` bits from one curve
+/// to another, e.g. to migrate a table keyed by Morton to Hilbert, without
+/// decoding to a list in between.
+///
+/// Returns the number of bytes written to `dest`, or a negative `LindelStatus`
+/// on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn transcode(
+    from_encoding: u8,
+    to_encoding: u8,
+    element_bit_width: u8,
+    dims: usize,
+    src: *const c_void,
+    dest: *mut c_void,
+) -> i32 {
+    if to_encoding > 1 {
+        return LindelStatus::InvalidEncodingType.into();
+    }
+
+    ffi_boundary(|| {
+        let key = match checked_key(from_encoding, element_bit_width, src, dims) {
+            Ok(key) => key,
+            Err(status) => return status.into(),
+        };
+        let mut values = [0u128; 1];
+        let values = values.as_mut_ptr() as *mut c_void;
+        let status = decode_key(from_encoding, element_bit_width, dims, key, values);
+        if status != LindelStatus::Ok {
+            return status.into();
+        }
+
+        // The encoders store the key aligned, so encode into scratch space.
+        let mut key = [0u128; 1];
+        let key_ptr = key.as_mut_ptr() as *mut c_void;
+        let written = encode_values(to_encoding, element_bit_width, values, dims, key_ptr);
+        if written < 0 {
+            return written;
+        }
+        assert!(!dest.is_null());
+        write_key(
+            dest,
+            written as usize * 8,
+            read_key(key_ptr, written as usize * 8),
+        );
+        written
+    })
+}
+
+/// Check whether two Hilbert keys of `dims` values of `element_bit_width`
+/// bits are the keys of spatially adjacent cells, which differ by exactly one
+/// in exactly one dimension.
+///
+/// Returns 1 if the cells are adjacent, 0 if they aren't, including when they
+/// are the same cell, or a negative `LindelStatus` on error.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hilbert_are_adjacent(
+    key_a: *const c_void,
+    key_b: *const c_void,
+    dims: usize,
+    element_bit_width: u8,
+) -> i32 {
+    ffi_boundary(|| {
+        let mut values = [[0u128; 1]; 2];
+        for (key, values) in [key_a, key_b].into_iter().zip(values.iter_mut()) {
+            let key = match read_checked_key(element_bit_width, dims, key) {
+                Ok(key) => key,
+                Err(status) => return status.into(),
+            };
+            let status = decode_key(
+                0,
+                element_bit_width,
+                dims,
+                key,
+                values.as_mut_ptr() as *mut c_void,
+            );
+            if status != LindelStatus::Ok {
+                return status.into();
+            }
+        }
+
+        let [a, b] = [0, 1].map(|i| values[i].as_ptr() as *const c_void);
+        let differences: Vec<u64> = (0..dims)
+            .map(|i| {
+                read_lane(a, element_bit_width, i).abs_diff(read_lane(b, element_bit_width, i))
+            })
+            .filter(|&difference| difference != 0)
+            .collect();
+        (differences == [1]) as i32
+    })
+}
+
+/// Encode `dims` values of `element_bit_width` bits and immediately decode the
+/// key again, as a self-test that the shape round trips.
+///
+/// `values` points to the raw bytes of the values in native byte order and
+/// doesn't need to be aligned.
+///
+/// Returns 1 if the decoded values exactly match `values` and 0 if they don't,
+/// or if the encoding type, width or number of dimensions isn't supported.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn lindel_roundtrip_check(
+    encoding_type: u8,
+    element_bit_width: u8,
+    values: *const c_void,
+    dims: usize,
+) -> i32 {
+    if values.is_null() || encoding_type > 1 || key_bits(element_bit_width, dims).is_none() {
+        return 0;
+    }
+
+    let len = dims * element_bit_width as usize / 8;
+    let round_trip = || {
+        // The typed encoders need aligned values, so copy them to scratch
+        // space as `encode_any()` does.
+        let mut scratch = [0u128; 1];
+        let scratch = scratch.as_mut_ptr() as *mut c_void;
+        std::ptr::copy_nonoverlapping(values as *const u8, scratch as *mut u8, len);
+
+        let mut key: u128 = 0;
+        let key_ptr = &mut key as *mut u128 as *mut c_void;
+        let written = encode_values(encoding_type, element_bit_width, scratch, dims, key_ptr);
+        if written < 0 {
+            return 0;
+        }
+
+        let mut decoded = [0u128; 1];
+        let status = perform_decode(
+            encoding_type,
+            element_bit_width,
+            key_ptr,
+            decoded.as_mut_ptr() as *mut c_void,
+            dims,
+        );
+        if status != LindelStatus::Ok {
+            return 0;
+        }
+
+        let expected = std::slice::from_raw_parts(values as *const u8, len);
+        let actual = std::slice::from_raw_parts(decoded.as_ptr() as *const u8, len);
+        (expected == actual) as i32
+    };
+
+    // A mismatch is the answer to the question being asked, so even a panic
+    // is reported as a failed round trip rather than a status.
+    ffi_boundary(round_trip).max(0)
+}
+
+#[cfg(test)]
+mod bench;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // DuckDB provides its allocator when the extension is loaded, the tests
+    // use the C allocator in its place.
+    extern "C" {
+        fn malloc(size: usize) -> *mut c_void;
+        fn free(ptr: *mut c_void);
+    }
+
+    #[no_mangle]
+    extern "C" fn duckdb_malloc(size: usize) -> *mut c_void {
+        unsafe { malloc(size) }
+    }
+
+    #[no_mangle]
+    extern "C" fn duckdb_free(ptr: *mut c_void) {
+        unsafe { free(ptr) }
+    }
+
+    #[test]
+    fn checked_decode_rejects_four_dimension_key_as_three() {
+        let values: [u32; 4] = [0xDEAD_BEEF, 0xCAFE_BABE, 0x1234_5678, 0x9ABC_DEF0];
+        for encoding_type in 0..2u8 {
+            let mut key: u128 = 0;
+            unsafe {
+                if encoding_type == 0 {
+                    hilbert_encode_u32_var(
+                        values.as_ptr(),
+                        4,
+                        &mut key as *mut u128 as *mut c_void,
+                        false,
+                    );
+                } else {
+                    morton_encode_u32_var(
+                        values.as_ptr(),
+                        4,
+                        &mut key as *mut u128 as *mut c_void,
+                        false,
+                    );
+                }
+            }
+
+            let mut three = [0u32; 3];
+            let status = unsafe {
+                perform_decode_checked(
+                    encoding_type,
+                    32,
+                    &key as *const u128 as *const c_void,
+                    three.as_mut_ptr() as *mut c_void,
+                    3,
+                )
+            };
+            assert_eq!(status, LindelStatus::KeyOutOfRange);
+            assert_eq!(three, [0, 0, 0]);
+
+            let mut four = [0u32; 4];
+            let status = unsafe {
+                perform_decode_checked(
+                    encoding_type,
+                    32,
+                    &key as *const u128 as *const c_void,
+                    four.as_mut_ptr() as *mut c_void,
+                    4,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            assert_eq!(four, values);
+        }
+    }
+
+    #[test]
+    fn checked_decode_rejects_invalid_shapes() {
+        let key: u128 = 0;
+        let mut dest = [0u32; 4];
+        let mut decode = |encoding_type, element_bit_width, dest_len| unsafe {
+            perform_decode_checked(
+                encoding_type,
+                element_bit_width,
+                &key as *const u128 as *const c_void,
+                dest.as_mut_ptr() as *mut c_void,
+                dest_len,
+            )
+        };
+        assert_eq!(decode(2, 32, 3), LindelStatus::InvalidEncodingType);
+        assert_eq!(decode(0, 24, 3), LindelStatus::InvalidElementBitWidth);
+        assert_eq!(decode(0, 32, 5), LindelStatus::InvalidDimensions);
+        assert_eq!(decode(0, 32, 0), LindelStatus::InvalidDimensions);
+    }
+
+    #[test]
+    fn decode_validates_width_and_encoding_type() {
+        let key: u128 = 22;
+        let mut dest = [0u64; 2];
+        let decode = |encoding_type, element_bit_width, dest: &mut [u64; 2]| {
+            perform_decode(
+                encoding_type,
+                element_bit_width,
+                &key as *const u128 as *const c_void,
+                dest.as_mut_ptr() as *mut c_void,
+                1,
+            )
+        };
+        for element_bit_width in [0, 7, 24, 128] {
+            assert_eq!(
+                decode(0, element_bit_width, &mut dest),
+                LindelStatus::InvalidElementBitWidth
+            );
+        }
+        assert_eq!(decode(2, 8, &mut dest), LindelStatus::InvalidEncodingType);
+        assert_eq!(dest, [0, 0]);
+        assert_eq!(decode(1, 8, &mut dest), LindelStatus::Ok);
+        assert_eq!(dest, [22, 0]);
+    }
+
+    #[test]
+    fn morton_common_prefix() {
+        let encode = |values: [u16; 3]| {
+            let mut key: u64 = 0;
+            unsafe {
+                morton_encode_u16_var(
+                    values.as_ptr(),
+                    3,
+                    &mut key as *mut u64 as *mut c_void,
+                    false,
+                );
+            }
+            key
+        };
+        let prefix = |a: u64, b: u64| unsafe {
+            morton_common_prefix_bits(
+                &a as *const u64 as *const c_void,
+                &b as *const u64 as *const c_void,
+                16,
+                3,
+            )
+        };
+
+        let a = encode([1000, 2000, 3000]);
+        assert_eq!(prefix(a, a), 48);
+
+        // Only the lowest bit of the last dimension differs, which is the
+        // last bit of the interleaved key.
+        let b = encode([1000, 2000, 3001]);
+        assert_eq!(prefix(a, b), 47);
+
+        // The most significant bit of the first dimension is the first bit
+        // of the interleaved key.
+        let c = encode([0x8000, 2000, 3000]);
+        assert_eq!(prefix(a, c), 0);
+
+        // Keys with bits outside of the shape are rejected.
+        assert_eq!(prefix(a, 1 << 50), LindelStatus::KeyOutOfRange as i32);
+        let mut narrow: u8 = 0;
+        unsafe {
+            assert_eq!(
+                morton_common_prefix_bits(
+                    &mut narrow as *mut u8 as *const c_void,
+                    &mut narrow as *mut u8 as *const c_void,
+                    8,
+                    17
+                ),
+                LindelStatus::InvalidDimensions as i32
+            );
+        }
+    }
+
+    #[test]
+    fn prefix_decode_clears_low_bits() {
+        let values: [u8; 2] = [0b1011_0110, 0b0110_1101];
+        for encoding_type in 0..2u8 {
+            let mut key: u16 = 0;
+            unsafe {
+                if encoding_type == 0 {
+                    hilbert_encode_u8_var(
+                        values.as_ptr(),
+                        2,
+                        &mut key as *mut u16 as *mut c_void,
+                        false,
+                    );
+                } else {
+                    morton_encode_u8_var(
+                        values.as_ptr(),
+                        2,
+                        &mut key as *mut u16 as *mut c_void,
+                        false,
+                    );
+                }
+            }
+            let truncated = key & 0xFF00;
+
+            let mut decoded = [0xFFu8; 2];
+            let status = unsafe {
+                perform_decode_prefix(
+                    encoding_type,
+                    8,
+                    8,
+                    &truncated as *const u16 as *const c_void,
+                    decoded.as_mut_ptr() as *mut c_void,
+                    2,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            assert_eq!(decoded, [0b1011_0000, 0b0110_0000]);
+
+            // All of the bits being significant is a normal decode.
+            let status = unsafe {
+                perform_decode_prefix(
+                    encoding_type,
+                    8,
+                    16,
+                    &key as *const u16 as *const c_void,
+                    decoded.as_mut_ptr() as *mut c_void,
+                    2,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            assert_eq!(decoded, values);
+        }
+
+        // Morton keys can be cut part way through a level, the leading
+        // dimension keeps the extra bit.
+        let mut key: u16 = 0;
+        unsafe {
+            morton_encode_u8_var(
+                values.as_ptr(),
+                2,
+                &mut key as *mut u16 as *mut c_void,
+                false,
+            );
+        }
+        let mut decoded = [0u8; 2];
+        let decode_prefix = |encoding_type, significant_bits, decoded: &mut [u8; 2]| unsafe {
+            perform_decode_prefix(
+                encoding_type,
+                8,
+                significant_bits,
+                &key as *const u16 as *const c_void,
+                decoded.as_mut_ptr() as *mut c_void,
+                2,
+            )
+        };
+        assert_eq!(decode_prefix(1, 3, &mut decoded), LindelStatus::Ok);
+        assert_eq!(decoded, [0b1000_0000, 0b0000_0000]);
+        assert_eq!(
+            decode_prefix(0, 3, &mut decoded),
+            LindelStatus::InvalidPrecision
+        );
+        assert_eq!(
+            decode_prefix(1, 17, &mut decoded),
+            LindelStatus::InvalidPrecision
+        );
+    }
+
+    #[test]
+    fn blob_keys_sort_like_integer_keys() {
+        let mut keys = Vec::new();
+        for x in (0..2000u32).step_by(97) {
+            for y in (0..70000u32).step_by(4099) {
+                let values = [x * 31337, y];
+                let mut key: u64 = 0;
+                let mut blob = [0u8; 16];
+                unsafe {
+                    hilbert_encode_u32_var(
+                        values.as_ptr(),
+                        2,
+                        &mut key as *mut u64 as *mut c_void,
+                        false,
+                    );
+                    assert_eq!(
+                        hilbert_encode_u32_var_blob(values.as_ptr(), 2, blob.as_mut_ptr()),
+                        16
+                    );
+                }
+                assert_eq!(blob, (key as u128).to_be_bytes());
+                keys.push((key, blob));
+            }
+        }
+
+        let mut by_key = keys.clone();
+        by_key.sort_by_key(|(key, _)| *key);
+        let mut by_blob = keys;
+        by_blob.sort_by_key(|(_, blob)| *blob);
+        assert_eq!(by_key, by_blob);
+
+        let mut blob = [0u8; 16];
+        let values = [1u64, 2, 3];
+        assert_eq!(
+            unsafe { morton_encode_u64_var_blob(values.as_ptr(), 3, blob.as_mut_ptr()) },
+            LindelStatus::InvalidDimensions.into()
+        );
+    }
+
+    #[test]
+    fn encode_returns_bytes_written() {
+        let mut result = [0u128; 1];
+        let out = result.as_mut_ptr() as *mut c_void;
+        let expected = |element_bit_width: u8, len: usize| {
+            key_bits(element_bit_width, len).unwrap() as i32 / 8
+        };
+        unsafe {
+            for len in 1..=16 {
+                let values = [0xA5u8; 16];
+                assert_eq!(
+                    hilbert_encode_u8_var(values.as_ptr(), len, out, false),
+                    expected(8, len)
+                );
+                assert_eq!(
+                    morton_encode_u8_var(values.as_ptr(), len, out, true),
+                    expected(8, len)
+                );
+            }
+            for len in 1..=8 {
+                let values = [0xA5A5u16; 8];
+                assert_eq!(
+                    hilbert_encode_u16_var(values.as_ptr(), len, out, false),
+                    expected(16, len)
+                );
+                assert_eq!(
+                    morton_encode_u16_var(values.as_ptr(), len, out, true),
+                    expected(16, len)
+                );
+            }
+            for len in 1..=4 {
+                let values = [0xA5A5_A5A5u32; 4];
+                assert_eq!(
+                    hilbert_encode_u32_var(values.as_ptr(), len, out, false),
+                    expected(32, len)
+                );
+                assert_eq!(
+                    morton_encode_u32_var(values.as_ptr(), len, out, true),
+                    expected(32, len)
+                );
+            }
+            for len in 1..=2 {
+                let values = [u64::MAX; 2];
+                assert_eq!(
+                    hilbert_encode_u64_var(values.as_ptr(), len, out, false),
+                    expected(64, len)
+                );
+                assert_eq!(
+                    morton_encode_u64_var(values.as_ptr(), len, out, true),
+                    expected(64, len)
+                );
+            }
+        }
+        assert_eq!(expected(8, 3), 4);
+        assert_eq!(expected(16, 2), 4);
+    }
+
+    #[test]
+    fn roundtrip_check() {
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        for encoding_type in 0..2u8 {
+            for _ in 0..50 {
+                let values = [xorshift(&mut state), xorshift(&mut state)];
+                let values = values.as_ptr() as *const c_void;
+                unsafe {
+                    for dims in 1..=16 {
+                        assert_eq!(lindel_roundtrip_check(encoding_type, 8, values, dims), 1);
+                    }
+                    for dims in 1..=8 {
+                        assert_eq!(lindel_roundtrip_check(encoding_type, 16, values, dims), 1);
+                    }
+                    for dims in 1..=4 {
+                        assert_eq!(lindel_roundtrip_check(encoding_type, 32, values, dims), 1);
+                    }
+                    for dims in 1..=2 {
+                        assert_eq!(lindel_roundtrip_check(encoding_type, 64, values, dims), 1);
+                    }
+                }
+            }
+        }
+
+        // Values that aren't aligned for their width still round trip.
+        let bytes: Vec<u8> = (0..33).collect();
+        let unaligned = bytes[1..].as_ptr() as *const c_void;
+        unsafe {
+            assert_eq!(lindel_roundtrip_check(0, 64, unaligned, 2), 1);
+            assert_eq!(lindel_roundtrip_check(1, 32, unaligned, 4), 1);
+            assert_eq!(lindel_roundtrip_check(0, 16, unaligned, 8), 1);
+        }
+
+        let values = [1u64, 2, 3];
+        let values = values.as_ptr() as *const c_void;
+        unsafe {
+            assert_eq!(lindel_roundtrip_check(0, 64, values, 3), 0);
+            assert_eq!(lindel_roundtrip_check(1, 8, values, 0), 0);
+            assert_eq!(lindel_roundtrip_check(1, 8, values, 17), 0);
+            assert_eq!(lindel_roundtrip_check(0, 12, values, 2), 0);
+            assert_eq!(lindel_roundtrip_check(2, 32, values, 2), 0);
+            assert_eq!(lindel_roundtrip_check(0, 32, std::ptr::null(), 2), 0);
+        }
+    }
+
+    #[test]
+    fn hilbert_range_membership() {
+        let ranges: [u64; 6] = [10, 20, 30, 30, 100, 200];
+        let in_ranges = |key| unsafe { hilbert_in_ranges(key, ranges.as_ptr(), 3) };
+        for key in [10, 15, 20, 30, 100, 150, 200] {
+            assert_eq!(in_ranges(key), 1, "key {}", key);
+        }
+        for key in [0, 9, 21, 29, 31, 99, 201, u64::MAX] {
+            assert_eq!(in_ranges(key), 0, "key {}", key);
+        }
+        assert_eq!(unsafe { hilbert_in_ranges(10, std::ptr::null(), 0) }, 0);
+    }
+
+    #[test]
+    fn morton_range_membership_batch() {
+        let ranges: [u64; 8] = [
+            5,
+            9,
+            1 << 20,
+            1 << 40,
+            1 << 50,
+            1 << 50,
+            u64::MAX - 3,
+            u64::MAX,
+        ];
+        let mut state = 0xD1B5_4A32_D192_ED03;
+        let keys: Vec<u64> = (0..100)
+            .map(|i| match i % 4 {
+                0 => xorshift(&mut state),
+                1 => xorshift(&mut state) >> 23,
+                2 => xorshift(&mut state) % 12,
+                _ => [1 << 50, u64::MAX, 1 << 20, 4][i / 4 % 4],
+            })
+            .collect();
+
+        let mut out = vec![0xFFu8; keys.len()];
+        let status = unsafe {
+            morton_in_ranges_batch(
+                keys.as_ptr(),
+                keys.len(),
+                ranges.as_ptr(),
+                4,
+                out.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, LindelStatus::Ok);
+        for (key, out) in keys.iter().zip(&out) {
+            let expected = ranges
+                .chunks(2)
+                .any(|range| (range[0]..=range[1]).contains(key));
+            assert_eq!(*out, u8::from(expected), "key {}", key);
+        }
+
+        let status = unsafe {
+            morton_in_ranges_batch(
+                keys.as_ptr(),
+                keys.len(),
+                std::ptr::null(),
+                0,
+                out.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, LindelStatus::Ok);
+        assert!(out.iter().all(|out| *out == 0));
+    }
+
+    #[test]
+    fn typed_decode_writes_the_encoded_bits() {
+        for encoding_type in 0..2u8 {
+            let signed: [i32; 3] = [-5, 7, i32::MIN];
+            let mut key: u128 = 0;
+            let key_ptr = &mut key as *mut u128 as *mut c_void;
+            unsafe {
+                if encoding_type == 0 {
+                    hilbert_encode_u32_var(signed.as_ptr() as *const u32, 3, key_ptr, false);
+                } else {
+                    morton_encode_u32_var(signed.as_ptr() as *const u32, 3, key_ptr, false);
+                }
+            }
+            let decode = |logical_type: u8, dest: *mut c_void| unsafe {
+                perform_decode_typed(encoding_type, 32, logical_type, false, key_ptr, dest, 3)
+            };
+
+            let mut as_unsigned = [0u32; 3];
+            let mut as_signed = [0i32; 3];
+            let mut as_float = [0f32; 3];
+            assert_eq!(
+                decode(0, as_unsigned.as_mut_ptr() as *mut c_void),
+                LindelStatus::Ok
+            );
+            assert_eq!(
+                decode(1, as_signed.as_mut_ptr() as *mut c_void),
+                LindelStatus::Ok
+            );
+            assert_eq!(
+                decode(2, as_float.as_mut_ptr() as *mut c_void),
+                LindelStatus::Ok
+            );
+            // The logical type doesn't change the bits that are written, the
+            // signed values come back because their bits were encoded as is.
+            assert_eq!(as_signed, signed);
+            assert_eq!(as_unsigned, signed.map(|v| v as u32));
+            assert_eq!(as_float.map(f32::to_bits), as_unsigned);
+
+            let floats: [f64; 2] = [-1.5, f64::INFINITY];
+            unsafe {
+                if encoding_type == 0 {
+                    hilbert_encode_u64_var(floats.as_ptr() as *const u64, 2, key_ptr, false);
+                } else {
+                    morton_encode_u64_var(floats.as_ptr() as *const u64, 2, key_ptr, false);
+                }
+            }
+            let mut decoded = [0f64; 2];
+            let status = unsafe {
+                perform_decode_typed(
+                    encoding_type,
+                    64,
+                    2,
+                    false,
+                    key_ptr,
+                    decoded.as_mut_ptr() as *mut c_void,
+                    2,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            assert_eq!(decoded, floats);
+        }
+
+        let key: u128 = 0;
+        let mut dest = [0u16; 2];
+        let mut typed = |element_bit_width, logical_type| unsafe {
+            perform_decode_typed(
+                0,
+                element_bit_width,
+                logical_type,
+                false,
+                &key as *const u128 as *const c_void,
+                dest.as_mut_ptr() as *mut c_void,
+                2,
+            )
+        };
+        assert_eq!(typed(16, 2), LindelStatus::InvalidLogicalType);
+        assert_eq!(typed(16, 3), LindelStatus::InvalidLogicalType);
+        assert_eq!(typed(12, 1), LindelStatus::InvalidElementBitWidth);
+    }
+
+    #[test]
+    fn hilbert_cell_bounds_at_half_precision() {
+        let values: [u16; 2] = [0x1234, 0xBEEF];
+        let mut key: u32 = 0;
+        unsafe {
+            hilbert_encode_u16_var(
+                values.as_ptr(),
+                2,
+                &mut key as *mut u32 as *mut c_void,
+                false,
+            );
+        }
+
+        let mut min = [0u16; 2];
+        let mut max = [0u16; 2];
+        let bounds = |level_bits, min: &mut [u16; 2], max: &mut [u16; 2]| unsafe {
+            hilbert_cell_bounds(
+                &key as *const u32 as *const c_void,
+                16,
+                2,
+                level_bits,
+                min.as_mut_ptr() as *mut c_void,
+                max.as_mut_ptr() as *mut c_void,
+            )
+        };
+
+        // Half of the 32 bits of the key leaves 8 bits per dimension, so the
+        // cell is 2^(16 - 16 / 2) on a side.
+        assert_eq!(bounds(16, &mut min, &mut max), LindelStatus::Ok);
+        assert_eq!(min, [0x1200, 0xBE00]);
+        assert_eq!(max, [0x12FF, 0xBEFF]);
+        for i in 0..2 {
+            assert_eq!(max[i] as u32 - min[i] as u32 + 1, 1 << (16 - 16 / 2));
+        }
+
+        assert_eq!(bounds(32, &mut min, &mut max), LindelStatus::Ok);
+        assert_eq!((min, max), (values, values));
+        assert_eq!(bounds(0, &mut min, &mut max), LindelStatus::Ok);
+        assert_eq!((min, max), ([0, 0], [u16::MAX, u16::MAX]));
+        assert_eq!(
+            bounds(15, &mut min, &mut max),
+            LindelStatus::InvalidPrecision
+        );
+
+        // A null pointer is rejected before anything is decoded.
+        let key_ptr = &key as *const u32 as *const c_void;
+        let min_ptr = min.as_mut_ptr() as *mut c_void;
+        let max_ptr = max.as_mut_ptr() as *mut c_void;
+        let null = std::ptr::null_mut();
+        for (key_ptr, min_ptr, max_ptr) in [
+            (null as *const c_void, min_ptr, max_ptr),
+            (key_ptr, null, max_ptr),
+            (key_ptr, min_ptr, null),
+        ] {
+            let status = unsafe { hilbert_cell_bounds(key_ptr, 16, 2, 16, min_ptr, max_ptr) };
+            assert_eq!(status, LindelStatus::NullPointer);
+        }
+        assert_eq!((min, max), ([0, 0], [u16::MAX, u16::MAX]));
+    }
+
+    #[test]
+    fn encoders_match_reference_for_every_dimension_count() {
+        // Interleave the values with the first in the most significant bit of
+        // each group.
+        fn morton_reference(values: &[u64], bits: u32) -> u128 {
+            let mut key = 0u128;
+            for bit in (0..bits).rev() {
+                for value in values {
+                    key = (key << 1) | ((value >> bit) & 1) as u128;
+                }
+            }
+            key
+        }
+
+        let mut state = 0x6A09_E667_F3BC_C908;
+        for element_bit_width in [8u8, 16, 32, 64] {
+            for dims in 1..=128 / element_bit_width as usize {
+                for _ in 0..20 {
+                    let values: Vec<u64> = (0..dims)
+                        .map(|_| xorshift(&mut state) >> (64 - element_bit_width))
+                        .collect();
+                    let mut packed = [0u128; 1];
+                    let packed = packed.as_mut_ptr() as *mut c_void;
+                    for (i, value) in values.iter().enumerate() {
+                        unsafe { write_lane(packed, element_bit_width, i, *value) };
+                    }
+
+                    for encoding_type in 0..2u8 {
+                        let mut key = [0u128; 1];
+                        let key_ptr = key.as_mut_ptr() as *mut c_void;
+                        let written = unsafe {
+                            encode_values(encoding_type, element_bit_width, packed, dims, key_ptr)
+                        };
+                        let bits = key_bits(element_bit_width, dims).unwrap();
+                        assert_eq!(written as usize * 8, bits);
+
+                        let expected = if encoding_type == 0 {
+                            hilbert::axes_to_index(&mut values.clone(), element_bit_width as u32)
+                        } else {
+                            morton_reference(&values, element_bit_width as u32)
+                        };
+                        assert_eq!(
+                            unsafe { read_key(key_ptr, bits) },
+                            expected,
+                            "encoding {} width {} dims {}",
+                            encoding_type,
+                            element_bit_width,
+                            dims
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hilbert_nearby_keys_are_distinct() {
+        let point: [u8; 2] = [100, 37];
+        let mut key: u16 = 0;
+        unsafe {
+            hilbert_encode_u8_var(
+                point.as_ptr(),
+                2,
+                &mut key as *mut u16 as *mut c_void,
+                false,
+            );
+        }
+        let nearby = |point: &[u8], k: usize| {
+            let mut out = vec![0u64; k];
+            let n = unsafe {
+                hilbert_nearby_keys(point.as_ptr() as *const c_void, 2, 8, k, out.as_mut_ptr())
+            };
+            out.truncate(n);
+            out
+        };
+
+        for k in [1, 3, 5, 20] {
+            let keys = nearby(&point, k);
+            assert_eq!(keys.len(), k);
+            assert_eq!(keys[0], key as u64);
+            let distinct: std::collections::HashSet<_> = keys.iter().collect();
+            assert_eq!(distinct.len(), k);
+        }
+
+        // Neighbors along the curve are also spatial neighbors, so both of
+        // them appear among the four cells that share a face with the point.
+        let keys = nearby(&point, 5);
+        assert!(keys.contains(&(key as u64 + 1)) && keys.contains(&(key as u64 - 1)));
+
+        // The first cell of the curve has no cell before it.
+        let keys = nearby(&[0u8, 0], 6);
+        assert_eq!(keys[0], 0);
+        assert_eq!(keys.len(), 6);
+        assert!(keys[1..].iter().all(|key| *key > 0));
+
+        // The number of keys is capped, and the walk along the curve never
+        // repeats a face neighbor.
+        let keys = nearby(&point, LINDEL_MAX_NEARBY_KEYS + 100);
+        assert_eq!(keys.len(), LINDEL_MAX_NEARBY_KEYS);
+        let distinct: std::collections::HashSet<_> = keys.iter().collect();
+        assert_eq!(distinct.len(), keys.len());
+
+        let values = [1u32, 2, 3];
+        let mut out = [0u64; 4];
+        let n = unsafe {
+            hilbert_nearby_keys(values.as_ptr() as *const c_void, 3, 32, 4, out.as_mut_ptr())
+        };
+        assert_eq!(n, 0);
+    }
+
+    #[test]
+    fn morton_packed_decode_round_trip() {
+        // The inverse of morton_decode_packed(), unpack the fields and encode.
+        fn encode_packed(packed: u128, dims: usize, field_bits: u8) -> u64 {
+            let mask = (1u128 << field_bits) - 1;
+            let values: Vec<u16> = (0..dims)
+                .map(|i| (packed >> ((dims - 1 - i) * field_bits as usize) & mask) as u16)
+                .collect();
+            let mut key: u64 = 0;
+            unsafe {
+                morton_encode_u16_var(
+                    values.as_ptr(),
+                    dims,
+                    &mut key as *mut u64 as *mut c_void,
+                    false,
+                );
+            }
+            key
+        }
+
+        let mut state = 0xBB67_AE85_84CA_A73B;
+        for dims in 1..=4 {
+            for _ in 0..100 {
+                let values: Vec<u16> = (0..dims)
+                    .map(|_| xorshift(&mut state) as u16 & 0x3FF)
+                    .collect();
+                let mut key: u64 = 0;
+                let mut packed: u128 = 0;
+                let status = unsafe {
+                    morton_encode_u16_var(
+                        values.as_ptr(),
+                        dims,
+                        &mut key as *mut u64 as *mut c_void,
+                        false,
+                    );
+                    morton_decode_packed(
+                        16,
+                        dims,
+                        &key as *const u64 as *const c_void,
+                        10,
+                        &mut packed as *mut u128 as *mut c_void,
+                    )
+                };
+                assert_eq!(status, LindelStatus::Ok);
+                assert!(packed >> (10 * dims) == 0);
+                assert_eq!(packed as u16 & 0x3FF, values[dims - 1]);
+                assert_eq!(encode_packed(packed, dims, 10), key);
+            }
+        }
+
+        let values: [u16; 2] = [0x400, 1];
+        let mut key: u32 = 0;
+        let mut packed: u128 = 0;
+        let status = unsafe {
+            morton_encode_u16_var(
+                values.as_ptr(),
+                2,
+                &mut key as *mut u32 as *mut c_void,
+                false,
+            );
+            morton_decode_packed(
+                16,
+                2,
+                &key as *const u32 as *const c_void,
+                10,
+                &mut packed as *mut u128 as *mut c_void,
+            )
+        };
+        assert_eq!(status, LindelStatus::ValueOutOfRange);
+    }
+
+    #[test]
+    fn transcode_round_trip() {
+        let mut state = 0x3C6E_F372_FE94_F82B;
+        for dims in 1..=8 {
+            let values: Vec<u16> = (0..dims).map(|_| xorshift(&mut state) as u16).collect();
+            let mut morton = [0u128; 1];
+            unsafe {
+                morton_encode_u16_var(
+                    values.as_ptr(),
+                    dims,
+                    morton.as_mut_ptr() as *mut c_void,
+                    false,
+                );
+            }
+
+            // Write to an unaligned destination to make sure that works too.
+            let mut hilbert = [0u8; 17];
+            let mut expected = [0u128; 1];
+            let mut back = [0u128; 1];
+            let written = unsafe {
+                hilbert_encode_u16_var(
+                    values.as_ptr(),
+                    dims,
+                    expected.as_mut_ptr() as *mut c_void,
+                    false,
+                );
+                transcode(
+                    1,
+                    0,
+                    16,
+                    dims,
+                    morton.as_ptr() as *const c_void,
+                    hilbert[1..].as_mut_ptr() as *mut c_void,
+                )
+            };
+            let bits = key_bits(16, dims).unwrap();
+            assert_eq!(written as usize * 8, bits);
+            assert_eq!(
+                unsafe { read_key(hilbert[1..].as_ptr() as *const c_void, bits) },
+                expected[0]
+            );
+
+            let written = unsafe {
+                transcode(
+                    0,
+                    1,
+                    16,
+                    dims,
+                    hilbert[1..].as_ptr() as *const c_void,
+                    back.as_mut_ptr() as *mut c_void,
+                )
+            };
+            assert_eq!(written as usize * 8, bits);
+            assert_eq!(back, morton, "dims {}", dims);
+        }
+
+        let key: u128 = 0;
+        let mut dest: u128 = 0;
+        let mut transcode_to = |to_encoding, dims| unsafe {
+            transcode(
+                1,
+                to_encoding,
+                16,
+                dims,
+                &key as *const u128 as *const c_void,
+                &mut dest as *mut u128 as *mut c_void,
+            )
+        };
+        assert_eq!(transcode_to(2, 2), LindelStatus::InvalidEncodingType.into());
+        assert_eq!(transcode_to(0, 9), LindelStatus::InvalidDimensions.into());
+    }
+
+    #[test]
+    fn float_encode_checks_non_finite_values() {
+        let mut key = [0u128; 1];
+        let out = key.as_mut_ptr() as *mut c_void;
+        let encode = |values: [f64; 2], float_checks: u8| {
+            let mut key = [0u128; 1];
+            let status = unsafe {
+                hilbert_encode_f64_var(
+                    values.as_ptr(),
+                    2,
+                    key.as_mut_ptr() as *mut c_void,
+                    false,
+                    float_checks,
+                )
+            };
+            (status, key[0])
+        };
+
+        let strict = LINDEL_REJECT_NAN | LINDEL_REJECT_INFINITE;
+        let rejected = i32::from(LindelStatus::NonFiniteValue);
+        assert_eq!(encode([1.0, f64::NAN], strict).0, rejected);
+        assert_eq!(encode([f64::NAN, 1.0], LINDEL_REJECT_NAN).0, rejected);
+        assert_eq!(
+            encode([f64::INFINITY, 1.0], LINDEL_REJECT_INFINITE).0,
+            rejected
+        );
+        assert_eq!(encode([1.0, f64::NEG_INFINITY], strict).0, rejected);
+        assert_eq!(encode([f64::INFINITY, 1.0], LINDEL_REJECT_NAN).0, 16);
+
+        // Leniently every NaN encodes as the standard quiet NaN.
+        let nan_bits = [1.0f64.to_bits(), f64::NAN.to_bits()];
+        unsafe { hilbert_encode_u64_var(nan_bits.as_ptr(), 2, out, false) };
+        for nan in [f64::NAN, -f64::NAN, f64::from_bits(0x7FF0_0000_0000_0001)] {
+            assert_eq!(encode([1.0, nan], 0), (16, key[0]));
+            assert_eq!(encode([1.0, nan], LINDEL_REJECT_INFINITE), (16, key[0]));
+        }
+
+        // Finite values encode exactly like their bits.
+        let values = [-2.5f32, 1e-3, 7.0];
+        let bits = values.map(f32::to_bits);
+        let mut float_key = [0u128; 1];
+        unsafe {
+            assert_eq!(morton_encode_u32_var(bits.as_ptr(), 3, out, true), 16);
+            assert_eq!(
+                morton_encode_f32_var(
+                    values.as_ptr(),
+                    3,
+                    float_key.as_mut_ptr() as *mut c_void,
+                    true,
+                    strict
+                ),
+                16
+            );
+        }
+        assert_eq!(float_key, key);
+        assert_eq!(
+            unsafe { morton_encode_f32_var(values.as_ptr(), 5, out, true, 0) },
+            LindelStatus::InvalidDimensions.into()
+        );
+    }
+
+    #[test]
+    fn bit_reversed_dimensions_round_trip() {
+        let mut state = 0xA54F_F53A_5F1D_36F1;
+        for encoding_type in 0..2u8 {
+            for dims in 1..=4 {
+                let values: Vec<u32> = (0..dims).map(|_| xorshift(&mut state) as u32).collect();
+                let values_ptr = values.as_ptr() as *const c_void;
+
+                let mut expected = [0u128; 1];
+                let mut key = [0u128; 1];
+                let key_ptr = key.as_mut_ptr() as *mut c_void;
+                unsafe {
+                    let written = encode_values(
+                        encoding_type,
+                        32,
+                        values_ptr,
+                        dims,
+                        expected.as_mut_ptr() as *mut c_void,
+                    );
+                    assert_eq!(
+                        lindel_encode_bit_reversed(encoding_type, 32, values_ptr, dims, 0, key_ptr),
+                        written
+                    );
+                }
+                assert_eq!(key, expected);
+
+                for reverse_mask in 1..1u32 << dims {
+                    let mut decoded = vec![0u32; dims];
+                    let status = unsafe {
+                        lindel_encode_bit_reversed(
+                            encoding_type,
+                            32,
+                            values_ptr,
+                            dims,
+                            reverse_mask,
+                            key_ptr,
+                        );
+                        perform_decode_bit_reversed(
+                            encoding_type,
+                            32,
+                            reverse_mask,
+                            key_ptr,
+                            decoded.as_mut_ptr() as *mut c_void,
+                            dims,
+                        )
+                    };
+                    assert_eq!(status, LindelStatus::Ok);
+                    assert_eq!(decoded, values);
+
+                    // Without restoring the bits the reversed dimensions
+                    // come back reversed.
+                    perform_decode(
+                        encoding_type,
+                        32,
+                        key_ptr,
+                        decoded.as_mut_ptr() as *mut c_void,
+                        dims,
+                    );
+                    for i in 0..dims {
+                        let expected = if reverse_mask & (1 << i) != 0 {
+                            values[i].reverse_bits()
+                        } else {
+                            values[i]
+                        };
+                        assert_eq!(decoded[i], expected);
+                    }
+                }
+            }
+        }
+
+        let values = [1u8, 2];
+        let mut key: u16 = 0;
+        let written = unsafe {
+            lindel_encode_bit_reversed(
+                0,
+                8,
+                values.as_ptr() as *const c_void,
+                2,
+                0b100,
+                &mut key as *mut u16 as *mut c_void,
+            )
+        };
+        assert_eq!(written, LindelStatus::InvalidDimensions.into());
+    }
+
+    #[test]
+    fn decode_table_round_trips_every_shape() {
+        let mut state = 0x510E_527F_ADE6_82D1;
+        for encoding_type in 0..2u8 {
+            for element_bit_width in [8u8, 16, 32, 64] {
+                for dims in 0..=17 {
+                    let values = [xorshift(&mut state), xorshift(&mut state)];
+                    let values = values.as_ptr() as *const c_void;
+                    let mut key = [0u128; 1];
+                    let key_ptr = key.as_mut_ptr() as *mut c_void;
+                    let mut decoded = [0u64; 3];
+                    let decoded = decoded.as_mut_ptr() as *mut c_void;
+                    let written = unsafe {
+                        encode_values(encoding_type, element_bit_width, values, dims, key_ptr)
+                    };
+                    let status =
+                        perform_decode(encoding_type, element_bit_width, key_ptr, decoded, dims);
+
+                    let shape = (encoding_type, element_bit_width, dims);
+                    if key_bits(element_bit_width, dims).is_none() {
+                        assert_eq!(status, LindelStatus::InvalidDimensions, "{:?}", shape);
+                        assert!(written < 0, "{:?}", shape);
+                        continue;
+                    }
+                    assert_eq!(status, LindelStatus::Ok, "{:?}", shape);
+                    for i in 0..dims {
+                        assert_eq!(
+                            unsafe { read_lane(decoded, element_bit_width, i) },
+                            unsafe { read_lane(values, element_bit_width, i) },
+                            "{:?} lane {}",
+                            shape,
+                            i
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn explicit_decode_resolves_ambiguous_shapes() {
+        // Each group of dimension counts shares a key width.
+        let ambiguous: [(u8, &[usize]); 6] = [
+            (8, &[3, 4]),
+            (8, &[5, 6, 7, 8]),
+            (8, &[9, 10, 11, 12, 13, 14, 15, 16]),
+            (16, &[3, 4]),
+            (16, &[5, 6, 7, 8]),
+            (32, &[3, 4]),
+        ];
+        let mut state = 0x9B05_688C_2B3E_6C1F;
+        for encoding_type in 0..2u8 {
+            for (element_bit_width, group) in ambiguous {
+                for &dims in group {
+                    // Set the top bit of every value.
+                    let mut values = [xorshift(&mut state), xorshift(&mut state)];
+                    let values = values.as_mut_ptr() as *mut c_void;
+                    for i in 0..dims {
+                        let value = unsafe { read_lane(values, element_bit_width, i) };
+                        let top_bit = 1 << (element_bit_width - 1);
+                        unsafe { write_lane(values, element_bit_width, i, value | top_bit) };
+                    }
+                    let mut key = [0u128; 1];
+                    let key_ptr = key.as_mut_ptr() as *mut c_void;
+                    unsafe {
+                        encode_values(encoding_type, element_bit_width, values, dims, key_ptr)
+                    };
+
+                    for &other in group {
+                        let mut decoded = [0u128; 1];
+                        let decoded = decoded.as_mut_ptr() as *mut c_void;
+                        let status = unsafe {
+                            perform_decode_explicit(
+                                encoding_type,
+                                element_bit_width,
+                                other as u8,
+                                key_ptr,
+                                decoded,
+                            )
+                        };
+                        let shape = (encoding_type, element_bit_width, dims, other);
+                        if other < dims {
+                            // The values' top bits are set, so the key
+                            // doesn't fit in fewer dimensions.
+                            assert_eq!(status, LindelStatus::KeyOutOfRange, "{:?}", shape);
+                            continue;
+                        }
+                        assert_eq!(status, LindelStatus::Ok, "{:?}", shape);
+                        let same = (0..dims).all(|i| unsafe {
+                            read_lane(decoded, element_bit_width, i)
+                                == read_lane(values, element_bit_width, i)
+                        });
+                        assert_eq!(same, other == dims, "{:?}", shape);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn checked_encode_respects_capacity() {
+        let values = [0xDEAD_BEEFu32, 0xCAFE_BABE, 0x1234_5678, 0x9ABC_DEF0];
+        let values_ptr = values.as_ptr() as *const c_void;
+        for encoding_type in 0..2u8 {
+            let mut expected = [0u128; 1];
+            unsafe {
+                encode_values(
+                    encoding_type,
+                    32,
+                    values_ptr,
+                    4,
+                    expected.as_mut_ptr() as *mut c_void,
+                );
+            }
+
+            // A 4 dimension u32 key needs 16 bytes, an 8 byte slot is refused
+            // without being touched.
+            let mut slot = [0xAAu8; 17];
+            let written = unsafe {
+                lindel_encode_checked(
+                    encoding_type,
+                    32,
+                    values_ptr,
+                    4,
+                    slot.as_mut_ptr() as *mut c_void,
+                    8,
+                )
+            };
+            assert_eq!(written, LindelStatus::BufferTooSmall.into());
+            assert_eq!(slot, [0xAA; 17]);
+
+            let written = unsafe {
+                lindel_encode_checked(
+                    encoding_type,
+                    32,
+                    values_ptr,
+                    4,
+                    slot[1..].as_mut_ptr() as *mut c_void,
+                    16,
+                )
+            };
+            assert_eq!(written, 16);
+            assert_eq!(slot[1..], expected[0].to_ne_bytes());
+            assert_eq!(slot[0], 0xAA);
+
+            let written = unsafe {
+                lindel_encode_checked(
+                    encoding_type,
+                    32,
+                    values_ptr,
+                    2,
+                    slot.as_mut_ptr() as *mut c_void,
+                    8,
+                )
+            };
+            assert_eq!(written, 8);
+        }
+    }
+
+    #[test]
+    fn encode_any_matches_typed_encoders() {
+        let mut state = 0x1F83_D9AB_FB41_BD6B;
+        for encoding_type in 0..2u8 {
+            for element_bit_width in [8u8, 16, 32, 64] {
+                for dims in 1..=128 / element_bit_width as usize {
+                    let words = [xorshift(&mut state), xorshift(&mut state)];
+                    let mut expected = [0u128; 1];
+                    let written = unsafe {
+                        encode_values(
+                            encoding_type,
+                            element_bit_width,
+                            words.as_ptr() as *const c_void,
+                            dims,
+                            expected.as_mut_ptr() as *mut c_void,
+                        )
+                    };
+
+                    // Pass unaligned values and result.
+                    let mut bytes = [0u8; 17];
+                    bytes[1..].copy_from_slice(
+                        &(words[0] as u128 | (words[1] as u128) << 64).to_ne_bytes(),
+                    );
+                    let mut result = [0u8; 17];
+                    let shape = (encoding_type, element_bit_width, dims);
+                    let any_written = unsafe {
+                        encode_any(
+                            encoding_type,
+                            element_bit_width,
+                            bytes[1..].as_ptr(),
+                            dims,
+                            result[1..].as_mut_ptr() as *mut c_void,
+                        )
+                    };
+                    assert_eq!(any_written, written, "{:?}", shape);
+                    let len = written as usize;
+                    assert_eq!(
+                        result[1..1 + len],
+                        expected[0].to_ne_bytes()[..len],
+                        "{:?}",
+                        shape
+                    );
+                }
+            }
+        }
+
+        let values = [0u8; 16];
+        let mut result = [0u128; 1];
+        let result = result.as_mut_ptr() as *mut c_void;
+        unsafe {
+            assert_eq!(
+                encode_any(2, 8, values.as_ptr(), 2, result),
+                LindelStatus::InvalidEncodingType.into()
+            );
+            assert_eq!(
+                encode_any(0, 12, values.as_ptr(), 2, result),
+                LindelStatus::InvalidElementBitWidth.into()
+            );
+            assert_eq!(
+                encode_any(1, 64, values.as_ptr(), 3, result),
+                LindelStatus::InvalidDimensions.into()
+            );
+        }
+    }
+
+    #[test]
+    fn hilbert_2d_with_explicit_order() {
+        let cells = [
+            (0, 0),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (1, 3),
+            (1, 2),
+            (2, 2),
+            (2, 3),
+            (3, 3),
+            (3, 2),
+            (3, 1),
+            (2, 1),
+            (2, 0),
+            (3, 0),
+        ];
+        for (d, (x, y)) in cells.into_iter().enumerate() {
+            assert_eq!(hilbert_xy2d(2, x, y), d as u64);
+            let (mut x_out, mut y_out) = (u32::MAX, u32::MAX);
+            assert_eq!(
+                unsafe { hilbert_d2xy(2, d as u64, &mut x_out, &mut y_out) },
+                LindelStatus::Ok
+            );
+            assert_eq!((x_out, y_out), (x, y));
+        }
+
+        assert_eq!(hilbert_xy2d(2, 4, 0), u64::MAX);
+        assert_eq!(hilbert_xy2d(0, 0, 0), u64::MAX);
+        assert_eq!(hilbert_xy2d(32, 0, 0), u64::MAX);
+        let (mut x, mut y) = (0, 0);
+        unsafe {
+            assert_eq!(
+                hilbert_d2xy(2, 16, &mut x, &mut y),
+                LindelStatus::ValueOutOfRange
+            );
+            assert_eq!(
+                hilbert_d2xy(32, 0, &mut x, &mut y),
+                LindelStatus::InvalidPrecision
+            );
+            assert_eq!(
+                hilbert_d2xy(31, (1 << 62) - 1, &mut x, &mut y),
+                LindelStatus::Ok
+            );
+        }
+        assert_eq!(hilbert_xy2d(31, x, y), (1 << 62) - 1);
+    }
+
+    #[test]
+    fn decode_into_allocated_buffer() {
+        let values: [u16; 3] = [0x1234, 0xFFFF, 7];
+        for encoding_type in 0..2u8 {
+            let mut key: u64 = 0;
+            unsafe {
+                encode_values(
+                    encoding_type,
+                    16,
+                    values.as_ptr() as *const c_void,
+                    3,
+                    &mut key as *mut u64 as *mut c_void,
+                );
+            }
+
+            let mut count = usize::MAX;
+            let decoded = unsafe {
+                perform_decode_alloc(
+                    encoding_type,
+                    16,
+                    3,
+                    &key as *const u64 as *const c_void,
+                    &mut count,
+                )
+            };
+            assert!(!decoded.is_null());
+            assert_eq!(count, 3);
+            assert_eq!(
+                unsafe { std::slice::from_raw_parts(decoded as *const u16, count) },
+                values
+            );
+            unsafe { super::duckdb_free(decoded) };
+
+            // An unsupported shape allocates nothing.
+            let decoded = unsafe {
+                perform_decode_alloc(
+                    encoding_type,
+                    16,
+                    9,
+                    &key as *const u64 as *const c_void,
+                    &mut count,
+                )
+            };
+            assert!(decoded.is_null());
+            assert_eq!(count, 0);
+        }
+    }
+
+    #[test]
+    fn transformed_dimensions() {
+        let values: [u16; 3] = [0x1234, 0xF00D, 0x0001];
+        let values_ptr = values.as_ptr() as *const c_void;
+        let identity: Vec<u64> = (0..16).map(|i| 1 << i).collect();
+        let reverse: Vec<u64> = (0..16).map(|i| 1 << (15 - i)).collect();
+
+        for encoding_type in 0..2u8 {
+            let transformed = |matrices: &[Vec<u64>], key: &mut u64| {
+                let matrices = matrices.concat();
+                unsafe {
+                    lindel_encode_transformed(
+                        encoding_type,
+                        16,
+                        values_ptr,
+                        3,
+                        matrices.as_ptr(),
+                        key as *mut u64 as *mut c_void,
+                    )
+                }
+            };
+
+            // The identity matches the plain encoder.
+            let mut expected: u64 = 0;
+            let mut key: u64 = 0;
+            unsafe {
+                encode_values(
+                    encoding_type,
+                    16,
+                    values_ptr,
+                    3,
+                    &mut expected as *mut u64 as *mut c_void,
+                );
+            }
+            let identities = [identity.clone(), identity.clone(), identity.clone()];
+            assert_eq!(transformed(&identities, &mut key), 8);
+            assert_eq!(key, expected);
+
+            // The anti-diagonal permutation reverses the bits of a dimension.
+            let matrices = [identity.clone(), reverse.clone(), identity.clone()];
+            unsafe {
+                lindel_encode_bit_reversed(
+                    encoding_type,
+                    16,
+                    values_ptr,
+                    3,
+                    0b010,
+                    &mut expected as *mut u64 as *mut c_void,
+                );
+            }
+            assert_eq!(transformed(&matrices, &mut key), 8);
+            assert_eq!(key, expected);
+
+            // Mix the bits of the last dimension, each bit with the one below.
+            let mixing: Vec<u64> = (0..16).map(|i| 3 << i & 0xFFFF).collect();
+            let matrices = [reverse.clone(), identity.clone(), mixing].concat();
+            let mut decoded = [0u16; 3];
+            let status = unsafe {
+                lindel_encode_transformed(
+                    encoding_type,
+                    16,
+                    values_ptr,
+                    3,
+                    matrices.as_ptr(),
+                    &mut key as *mut u64 as *mut c_void,
+                );
+                perform_decode_transformed(
+                    encoding_type,
+                    16,
+                    matrices.as_ptr(),
+                    &key as *const u64 as *const c_void,
+                    decoded.as_mut_ptr() as *mut c_void,
+                    3,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            assert_eq!(decoded, values);
+
+            let mut singular = identity.clone();
+            singular[0] = 0;
+            let matrices = [identity.clone(), singular, identity.clone()];
+            assert_eq!(
+                transformed(&matrices, &mut key),
+                LindelStatus::SingularMatrix.into()
+            );
+        }
+    }
+
+    #[test]
+    fn morton_uneven_widths() {
+        let widths = [20u8, 12];
+        let encode = |values: [u64; 2]| {
+            let mut key: u32 = 0;
+            let written = unsafe {
+                morton_encode_uneven(
+                    values.as_ptr(),
+                    widths.as_ptr(),
+                    2,
+                    &mut key as *mut u32 as *mut c_void,
+                )
+            };
+            assert_eq!(written, 4);
+            key
+        };
+
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        for _ in 0..200 {
+            let r = xorshift(&mut state);
+            let values = [r & 0xF_FFFF, (r >> 32) & 0xFFF];
+            let key = encode(values);
+            let mut decoded = [0u64; 2];
+            let status = unsafe {
+                morton_decode_uneven(
+                    widths.as_ptr(),
+                    2,
+                    &key as *const u32 as *const c_void,
+                    decoded.as_mut_ptr(),
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            assert_eq!(decoded, values);
+        }
+
+        // The top bit of X comes first, then the top bit of Y, and the low
+        // 8 bits of X come last once Y is exhausted.
+        assert_eq!(encode([1 << 19, 0]), 1 << 31);
+        assert_eq!(encode([0, 1 << 11]), 1 << 30);
+        assert_eq!(encode([0xFF, 0]), 0xFF);
+        assert_eq!(encode([0x100, 0]), 1 << 9);
+        assert!(encode([1 << 19, 0]) > encode([0x7_FFFF, 0xFFF]));
+
+        let mut key = 0u32;
+        let too_big = [1u64 << 20, 0];
+        let status = unsafe {
+            morton_encode_uneven(
+                too_big.as_ptr(),
+                widths.as_ptr(),
+                2,
+                &mut key as *mut u32 as *mut c_void,
+            )
+        };
+        assert_eq!(status, LindelStatus::ValueOutOfRange.into());
+        let bad_widths = [0u8, 12];
+        let status = unsafe {
+            morton_encode_uneven(
+                too_big.as_ptr(),
+                bad_widths.as_ptr(),
+                2,
+                &mut key as *mut u32 as *mut c_void,
+            )
+        };
+        assert_eq!(status, LindelStatus::InvalidPrecision.into());
+    }
+
+    #[test]
+    fn hilbert_distance_along_the_curve() {
+        let distance = |a: [u32; 2], b: [u32; 2]| {
+            let mut distance: u128 = 0;
+            let status = unsafe {
+                hilbert_distance(
+                    a.as_ptr() as *const c_void,
+                    b.as_ptr() as *const c_void,
+                    2,
+                    32,
+                    &mut distance as *mut u128 as *mut c_void,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok.into());
+            distance
+        };
+
+        // Consecutive cells of the curve are a distance of one apart.
+        for d in 0..64u64 {
+            let (x, y) = hilbert::d_to_xy(32, d);
+            let (next_x, next_y) = hilbert::d_to_xy(32, d + 1);
+            let a = [x as u32, y as u32];
+            let b = [next_x as u32, next_y as u32];
+            assert_eq!(distance(a, b), 1);
+            assert_eq!(distance(b, a), 1);
+        }
+        assert_eq!(distance([7, 9], [7, 9]), 0);
+
+        // The curve starts at the origin and ends in the opposite quadrant
+        // along the x axis, the last of the 2^64 cells.
+        assert_eq!(distance([0, 0], [u32::MAX, 0]), u64::MAX as u128);
+        let quarter = 1u128 << 62;
+        assert_eq!(distance([0, 0], [0, 1 << 31]), quarter);
+
+        let values = [u64::MAX, u64::MAX];
+        let mut result: u128 = 0;
+        let status = unsafe {
+            hilbert_distance(
+                values.as_ptr() as *const c_void,
+                [0u64, 0].as_ptr() as *const c_void,
+                2,
+                64,
+                &mut result as *mut u128 as *mut c_void,
+            )
+        };
+        assert_eq!(status, LindelStatus::Ok.into());
+        assert_eq!(result, lindel::hilbert_encode([u64::MAX, u64::MAX]));
+        let status = unsafe {
+            hilbert_distance(
+                values.as_ptr() as *const c_void,
+                values.as_ptr() as *const c_void,
+                3,
+                64,
+                &mut result as *mut u128 as *mut c_void,
+            )
+        };
+        assert_eq!(status, LindelStatus::InvalidDimensions.into());
+    }
+
+    #[test]
+    fn morton_wide_round_trip() {
+        let round_trip = |values: &[u64]| {
+            let mut bytes = [0u8; 32];
+            let written = unsafe {
+                morton_encode_wide(values.as_ptr(), values.len(), bytes.as_mut_ptr(), 32)
+            };
+            assert_eq!(written as usize, values.len() * 8);
+            let mut decoded = [0u64; 4];
+            let status = unsafe {
+                morton_decode_wide(
+                    bytes.as_ptr(),
+                    written as usize,
+                    values.len(),
+                    decoded.as_mut_ptr(),
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            assert_eq!(&decoded[..values.len()], values);
+            bytes
+        };
+
+        let bytes = round_trip(&[u64::MAX; 4]);
+        assert_eq!(bytes, [0xFF; 32]);
+        let bytes = round_trip(&[u64::MAX, 0, 0, 0]);
+        assert_eq!(bytes, [0x88; 32]);
+        round_trip(&[0, 0, 0, u64::MAX]);
+        round_trip(&[0; 4]);
+
+        let mut state: u64 = 0xC2B2_AE3D_27D4_EB4F;
+        for _ in 0..100 {
+            let values: Vec<u64> = (0..4).map(|_| xorshift(&mut state)).collect();
+            round_trip(&values);
+            round_trip(&values[..3]);
+
+            // Matches the u128 key of two dimensions.
+            let bytes = round_trip(&values[..2]);
+            let key = lindel::morton_encode([values[0], values[1]]);
+            assert_eq!(bytes[..16], key.to_be_bytes());
+        }
+
+        let values = [1u64; 4];
+        let mut bytes = [0u8; 32];
+        let status = unsafe { morton_encode_wide(values.as_ptr(), 4, bytes.as_mut_ptr(), 31) };
+        assert_eq!(status, LindelStatus::BufferTooSmall.into());
+        let status = unsafe { morton_encode_wide(values.as_ptr(), 5, bytes.as_mut_ptr(), 40) };
+        assert_eq!(status, LindelStatus::InvalidDimensions.into());
+    }
+
+    #[test]
+    fn hilbert_adjacent_cells() {
+        let key = |values: [u16; 3]| lindel::hilbert_encode(values);
+        let adjacent = |a: [u16; 3], b: [u16; 3]| unsafe {
+            hilbert_are_adjacent(
+                &key(a) as *const u64 as *const c_void,
+                &key(b) as *const u64 as *const c_void,
+                3,
+                16,
+            )
+        };
+
+        assert_eq!(adjacent([5, 9, 200], [5, 10, 200]), 1);
+        assert_eq!(adjacent([6, 9, 200], [5, 9, 200]), 1);
+        assert_eq!(adjacent([0, 0, u16::MAX], [0, 0, u16::MAX - 1]), 1);
+        assert_eq!(adjacent([5, 9, 200], [6, 10, 200]), 0);
+        assert_eq!(adjacent([5, 9, 200], [5, 11, 200]), 0);
+        assert_eq!(adjacent([5, 9, 200], [5, 9, 200]), 0);
+
+        // Consecutive keys along the curve are always adjacent cells.
+        for index in 0..1000u64 {
+            let (a, b) = (index, index + 1);
+            let status = unsafe {
+                hilbert_are_adjacent(
+                    &a as *const u64 as *const c_void,
+                    &b as *const u64 as *const c_void,
+                    3,
+                    16,
+                )
+            };
+            assert_eq!(status, 1);
+        }
+
+        let out_of_range = 1u64 << 50;
+        let status = unsafe {
+            hilbert_are_adjacent(
+                &out_of_range as *const u64 as *const c_void,
+                &out_of_range as *const u64 as *const c_void,
+                3,
+                16,
+            )
+        };
+        assert_eq!(status, LindelStatus::KeyOutOfRange.into());
+    }
+
+    #[test]
+    fn decimal_encode_preserves_numeric_order() {
+        // DECIMAL(18,4) prices as their scaled integers.
+        let prices: [i128; 7] = [
+            -999_999_999_999_999_999,
+            -123_450_000,
+            -1,
+            0,
+            1,
+            425_000,
+            999_999_999_999_999_999,
+        ];
+
+        let encode = |values: &[i128]| {
+            let values: Vec<LindelHugeint> = values.iter().map(|&v| v.into()).collect();
+            let mut key: u128 = 0;
+            let written = unsafe {
+                hilbert_encode_decimal_var(
+                    values.as_ptr(),
+                    4,
+                    values.len(),
+                    &mut key as *mut u128 as *mut c_void,
+                )
+            };
+            (written, key)
+        };
+        let decode = |key: u128, dims: usize| {
+            let mut decoded = [LindelHugeint::default(); 2];
+            let status = unsafe {
+                hilbert_decode_decimal(
+                    &key as *const u128 as *const c_void,
+                    4,
+                    dims,
+                    decoded.as_mut_ptr(),
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            decoded[..dims]
+                .iter()
+                .map(|&v| i128::from(v))
+                .collect::<Vec<_>>()
+        };
+
+        // A single DECIMAL(38) uses the whole key.
+        let limit = 10i128.pow(38) - 1;
+        let wide = [
+            i128::MIN,
+            -limit,
+            -(1 << 64),
+            -1,
+            0,
+            1,
+            u64::MAX as i128,
+            limit,
+            i128::MAX,
+        ];
+        let keys: Vec<u128> = wide
+            .iter()
+            .chain(&prices)
+            .map(|&value| {
+                let (written, key) = encode(&[value]);
+                assert_eq!(written, 16);
+                assert_eq!(decode(key, 1), [value]);
+                key
+            })
+            .collect();
+        assert!(keys[..wide.len()].windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(keys[wide.len()..].windows(2).all(|pair| pair[0] < pair[1]));
+
+        for a in prices {
+            for b in prices {
+                let (written, key) = encode(&[a, b]);
+                assert_eq!(written, 16);
+                assert_eq!(decode(key, 2), [a, b]);
+            }
+        }
+
+        // Two values only fit in the key when each fits in 64 bits.
+        let too_big = i64::MAX as i128 + 1;
+        assert_eq!(
+            encode(&[0, too_big]).0,
+            LindelStatus::ValueOutOfRange.into()
+        );
+        assert_eq!(
+            encode(&[i64::MIN as i128 - 1, 0]).0,
+            LindelStatus::ValueOutOfRange.into()
+        );
+
+        let values = [LindelHugeint::from(0); 3];
+        let mut key: u128 = 0;
+        let key_ptr = &mut key as *mut u128 as *mut c_void;
+        let status = unsafe { hilbert_encode_decimal_var(values.as_ptr(), 39, 1, key_ptr) };
+        assert_eq!(status, LindelStatus::InvalidPrecision.into());
+        let status = unsafe { hilbert_encode_decimal_var(values.as_ptr(), 4, 3, key_ptr) };
+        assert_eq!(status, LindelStatus::InvalidDimensions.into());
+    }
+
+    #[test]
+    fn encode_from_bytes_in_either_byte_order() {
+        let bytes: [u8; 6] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        for encoding_type in 0..2u8 {
+            let from_bytes = |byte_order: u8| {
+                let mut key: u64 = 0;
+                let written = unsafe {
+                    encode_from_bytes(
+                        encoding_type,
+                        16,
+                        3,
+                        byte_order,
+                        bytes.as_ptr(),
+                        &mut key as *mut u64 as *mut c_void,
+                    )
+                };
+                assert_eq!(written, 8);
+                key
+            };
+            let encode = |values: [u16; 3]| {
+                let mut key: u64 = 0;
+                unsafe {
+                    encode_values(
+                        encoding_type,
+                        16,
+                        values.as_ptr() as *const c_void,
+                        3,
+                        &mut key as *mut u64 as *mut c_void,
+                    );
+                }
+                key
+            };
+
+            let little = from_bytes(LINDEL_LITTLE_ENDIAN);
+            let big = from_bytes(LINDEL_BIG_ENDIAN);
+            assert_ne!(little, big);
+            assert_eq!(little, encode([0x0201, 0x0403, 0x0605]));
+            assert_eq!(big, encode([0x0102, 0x0304, 0x0506]));
+        }
+
+        let mut key: u64 = 0;
+        let status = unsafe {
+            encode_from_bytes(
+                0,
+                16,
+                3,
+                2,
+                bytes.as_ptr(),
+                &mut key as *mut u64 as *mut c_void,
+            )
+        };
+        assert_eq!(status, LindelStatus::InvalidByteOrder.into());
+    }
+
+    #[test]
+    fn zero_dimensions_are_rejected() {
+        let invalid = i32::from(LindelStatus::InvalidDimensions);
+        let mut key = [0u128; 2];
+        let key_ptr = key.as_mut_ptr() as *mut c_void;
+        let src = key.as_ptr() as *const c_void;
+        let mut dest = [0u64; 4];
+        let dest_ptr = dest.as_mut_ptr() as *mut c_void;
+        let mut count = 0usize;
+
+        unsafe {
+            // The encoders, given the null pointer an empty list may have.
+            assert_eq!(
+                hilbert_encode_u8_var(std::ptr::null(), 0, key_ptr, false),
+                invalid
+            );
+            assert_eq!(
+                morton_encode_u8_var(std::ptr::null(), 0, key_ptr, false),
+                invalid
+            );
+            assert_eq!(
+                hilbert_encode_u16_var(std::ptr::null(), 0, key_ptr, false),
+                invalid
+            );
+            assert_eq!(
+                morton_encode_u16_var(std::ptr::null(), 0, key_ptr, false),
+                invalid
+            );
+            assert_eq!(
+                hilbert_encode_u32_var(std::ptr::null(), 0, key_ptr, false),
+                invalid
+            );
+            assert_eq!(
+                morton_encode_u32_var(std::ptr::null(), 0, key_ptr, false),
+                invalid
+            );
+            assert_eq!(
+                hilbert_encode_u64_var(std::ptr::null(), 0, key_ptr, false),
+                invalid
+            );
+            assert_eq!(
+                morton_encode_u64_var(std::ptr::null(), 0, key_ptr, false),
+                invalid
+            );
+            assert_eq!(
+                hilbert_encode_u64_var_blob(std::ptr::null(), 0, key_ptr as *mut u8),
+                invalid
+            );
+            assert_eq!(
+                morton_encode_u8_var_blob(std::ptr::null(), 0, key_ptr as *mut u8),
+                invalid
+            );
+            assert_eq!(
+                hilbert_encode_f32_var(std::ptr::null(), 0, key_ptr, false, 0),
+                invalid
+            );
+            assert_eq!(
+                morton_encode_f64_var(std::ptr::null(), 0, key_ptr, false, 0),
+                invalid
+            );
+            assert_eq!(encode_any(0, 32, std::ptr::null(), 0, key_ptr), invalid);
+            assert_eq!(
+                encode_from_bytes(1, 32, 0, LINDEL_BIG_ENDIAN, std::ptr::null(), key_ptr),
+                invalid
+            );
+            assert_eq!(
+                lindel_encode_checked(0, 32, std::ptr::null(), 0, key_ptr, 16),
+                invalid
+            );
+            assert_eq!(
+                lindel_encode_bit_reversed(1, 16, std::ptr::null(), 0, 0, key_ptr),
+                invalid
+            );
+            assert_eq!(
+                lindel_encode_transformed(0, 8, std::ptr::null(), 0, std::ptr::null(), key_ptr),
+                invalid
+            );
+            assert_eq!(
+                hilbert_encode_decimal_var(std::ptr::null(), 2, 0, key_ptr),
+                invalid
+            );
+            assert_eq!(
+                morton_encode_wide(std::ptr::null(), 0, key_ptr as *mut u8, 32),
+                invalid
+            );
+            assert_eq!(
+                morton_encode_uneven(std::ptr::null(), std::ptr::null(), 0, key_ptr),
+                invalid
+            );
+            assert_eq!(
+                hilbert_encode_u64_bits(std::ptr::null(), 0, 8, key_ptr),
+                LindelStatus::InvalidDimensions
+            );
+            assert_eq!(
+                hilbert_distance(std::ptr::null(), std::ptr::null(), 0, 16, key_ptr),
+                invalid
+            );
+            assert_eq!(lindel_roundtrip_check(0, 8, std::ptr::null(), 0), 0);
+            assert_eq!(
+                hilbert_nearby_keys(dest_ptr, 0, 16, 4, dest.as_mut_ptr()),
+                0
+            );
+
+            // The decoders.
+            for encoding_type in 0..2u8 {
+                for width in [8, 16, 32, 64] {
+                    let status = LindelStatus::InvalidDimensions;
+                    assert_eq!(
+                        perform_decode(encoding_type, width, src, dest_ptr, 0),
+                        status
+                    );
+                    assert_eq!(
+                        perform_decode_checked(encoding_type, width, src, dest_ptr, 0),
+                        status
+                    );
+                    assert_eq!(
+                        perform_decode_explicit(encoding_type, width, 0, src, dest_ptr),
+                        status
+                    );
+                    assert_eq!(
+                        perform_decode_typed(encoding_type, width, 0, false, src, dest_ptr, 0),
+                        status
+                    );
+                    assert_eq!(
+                        perform_decode_prefix(encoding_type, width, 0, src, dest_ptr, 0),
+                        status
+                    );
+                    assert_eq!(
+                        perform_decode_bit_reversed(encoding_type, width, 0, src, dest_ptr, 0),
+                        status
+                    );
+                    assert_eq!(
+                        perform_decode_transformed(
+                            encoding_type,
+                            width,
+                            std::ptr::null(),
+                            src,
+                            dest_ptr,
+                            0
+                        ),
+                        status
+                    );
+                    assert_eq!(
+                        transcode(encoding_type, 1 - encoding_type, width, 0, src, key_ptr),
+                        invalid
+                    );
+                    assert!(
+                        perform_decode_alloc(encoding_type, width, 0, src, &mut count).is_null()
+                    );
+                    assert_eq!(morton_common_prefix_bits(src, src, width, 0), invalid);
+                    assert_eq!(hilbert_are_adjacent(src, src, 0, width), invalid);
+                }
+            }
+            assert_eq!(
+                morton_decode_packed(16, 0, src, 8, dest_ptr),
+                LindelStatus::InvalidDimensions
+            );
+            assert_eq!(
+                hilbert_cell_bounds(src, 16, 0, 8, dest_ptr, dest_ptr),
+                LindelStatus::InvalidDimensions
+            );
+            assert_eq!(
+                hilbert_decode_u64_bits(src, 0, 8, dest.as_mut_ptr()),
+                LindelStatus::InvalidDimensions
+            );
+            assert_eq!(
+                hilbert_decode_decimal(src, 2, 0, std::ptr::null_mut()),
+                LindelStatus::InvalidDimensions
+            );
+            assert_eq!(
+                morton_decode_wide(std::ptr::null(), 0, 0, dest.as_mut_ptr()),
+                LindelStatus::InvalidDimensions
+            );
+            assert_eq!(
+                morton_decode_uneven(std::ptr::null(), 0, src, dest.as_mut_ptr()),
+                LindelStatus::InvalidDimensions
+            );
+        }
+    }
+
+    #[test]
+    fn hilbert_soa_matches_per_row_encodes() {
+        let mut state: u64 = 0xD1B5_4A32_D192_ED03;
+        let columns: Vec<Vec<u32>> = (0..4)
+            .map(|_| (0..1000).map(|_| xorshift(&mut state) as u32).collect())
+            .collect();
+
+        for dims in 1..=4 {
+            let dim_ptrs: Vec<*const u32> = columns[..dims].iter().map(|c| c.as_ptr()).collect();
+            let mut keys = vec![0u8; 1000 * 16];
+            let key_bytes = unsafe {
+                hilbert_encode_soa(
+                    dim_ptrs.as_ptr(),
+                    dims,
+                    1000,
+                    keys.as_mut_ptr() as *mut c_void,
+                )
+            };
+            assert_eq!(key_bytes, (dims * 32).next_power_of_two() as i32 / 8);
+            let key_bytes = key_bytes as usize;
+
+            for i in 0..1000 {
+                let row: Vec<u32> = columns[..dims].iter().map(|c| c[i]).collect();
+                let mut expected = [0u128; 1];
+                unsafe {
+                    hilbert_encode_u32_var(
+                        row.as_ptr(),
+                        dims,
+                        expected.as_mut_ptr() as *mut c_void,
+                        false,
+                    );
+                }
+                let expected = unsafe {
+                    std::slice::from_raw_parts(expected.as_ptr() as *const u8, key_bytes)
+                };
+                assert_eq!(&keys[i * key_bytes..(i + 1) * key_bytes], expected);
+            }
+        }
+
+        let status = unsafe { hilbert_encode_soa(std::ptr::null(), 5, 1, std::ptr::null_mut()) };
+        assert_eq!(status, LindelStatus::InvalidDimensions.into());
+    }
+
+    #[test]
+    fn morton_uneven_mixed_signedness() {
+        // A signed i16 delta and an unsigned u32 id.
+        let widths = [16u8, 32];
+        let encode = |values: [i128; 2]| {
+            let values = values.map(LindelHugeint::from);
+            let mut key: u64 = 0;
+            let written = unsafe {
+                morton_encode_uneven_signed(
+                    values.as_ptr(),
+                    widths.as_ptr(),
+                    0b01,
+                    2,
+                    &mut key as *mut u64 as *mut c_void,
+                )
+            };
+            assert_eq!(written, 8);
+            key
+        };
+        let decode = |key: u64| {
+            let mut values = [LindelHugeint::default(); 2];
+            let status = unsafe {
+                morton_decode_uneven_signed(
+                    widths.as_ptr(),
+                    0b01,
+                    2,
+                    &key as *const u64 as *const c_void,
+                    values.as_mut_ptr(),
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            values.map(i128::from)
+        };
+
+        assert_eq!(decode(encode([-3, 100])), [-3, 100]);
+        for values in [
+            [i16::MIN as i128, 0],
+            [i16::MAX as i128, u32::MAX as i128],
+            [0, 1],
+        ] {
+            assert_eq!(decode(encode(values)), values);
+        }
+
+        // Each dimension is ordered by its own signedness.
+        let deltas = [i16::MIN as i128, -3, -1, 0, 1, i16::MAX as i128];
+        let keys: Vec<u64> = deltas.iter().map(|&delta| encode([delta, 100])).collect();
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        let ids = [0, 1, 100, 1 << 31, u32::MAX as i128];
+        let keys: Vec<u64> = ids.iter().map(|&id| encode([-3, id])).collect();
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let mut key: u64 = 0;
+        let key_ptr = &mut key as *mut u64 as *mut c_void;
+        for values in [
+            [i16::MAX as i128 + 1, 0],
+            [0, -1],
+            [0, u32::MAX as i128 + 1],
+            [i128::MAX, 0],
+            [i128::MIN, 0],
+            [0, i128::MAX],
+            [0, i128::MIN],
+        ] {
+            let values = values.map(LindelHugeint::from);
+            let status = unsafe {
+                morton_encode_uneven_signed(values.as_ptr(), widths.as_ptr(), 0b01, 2, key_ptr)
+            };
+            assert_eq!(status, LindelStatus::ValueOutOfRange.into());
+        }
+        let status = unsafe {
+            morton_encode_uneven_signed(
+                [LindelHugeint::default(); 2].as_ptr(),
+                widths.as_ptr(),
+                0b100,
+                2,
+                key_ptr,
+            )
+        };
+        assert_eq!(status, LindelStatus::InvalidDimensions.into());
+    }
+
+    #[test]
+    fn validate_key_bits_within_shape() {
+        let validate = |key: u128, bits: u8, dims: usize, width: u8| unsafe {
+            lindel_validate_key(&key as *const u128 as *const c_void, bits, dims, width)
+        };
+
+        // Three 16 bit values use the low 48 bits of a u64.
+        let key = lindel::hilbert_encode([u16::MAX; 3]) as u128;
+        assert_eq!(validate(key, 64, 3, 16), 1);
+        assert_eq!(validate(key | 1 << 48, 64, 3, 16), 0);
+        assert_eq!(validate(1 << 47, 64, 3, 16), 1);
+        assert_eq!(validate(1 << 63, 64, 4, 16), 1);
+        assert_eq!(validate(1 << 63, 64, 3, 16), 0);
+        assert_eq!(validate(0, 8, 1, 8), 1);
+
+        // Every bit of a full 128 bit key is in range.
+        assert_eq!(validate(u128::MAX, 128, 2, 64), 1);
+        assert_eq!(validate(u128::MAX, 128, 4, 32), 1);
+        assert_eq!(validate(u128::MAX, 128, 3, 32), 0);
+
+        assert_eq!(
+            validate(0, 24, 3, 16),
+            LindelStatus::InvalidPrecision.into()
+        );
+        assert_eq!(
+            validate(0, 64, 3, 12),
+            LindelStatus::InvalidElementBitWidth.into()
+        );
+        assert_eq!(
+            validate(0, 64, 3, 64),
+            LindelStatus::InvalidDimensions.into()
+        );
+    }
+
+    #[test]
+    fn narrowing_decode_detects_truncation() {
+        for encoding_type in 0..2u8 {
+            let encode = |values: [u16; 3]| {
+                let mut key: u64 = 0;
+                unsafe {
+                    encode_values(
+                        encoding_type,
+                        16,
+                        values.as_ptr() as *const c_void,
+                        3,
+                        &mut key as *mut u64 as *mut c_void,
+                    );
+                }
+                key
+            };
+            let narrowed = |key: u64, dest: &mut [u8; 3]| unsafe {
+                perform_decode_narrowing(
+                    encoding_type,
+                    16,
+                    8,
+                    &key as *const u64 as *const c_void,
+                    dest.as_mut_ptr() as *mut c_void,
+                    3,
+                )
+            };
+
+            let mut dest = [0u8; 3];
+            assert_eq!(narrowed(encode([1, 255, 0]), &mut dest), LindelStatus::Ok);
+            assert_eq!(dest, [1, 255, 0]);
+            assert_eq!(
+                narrowed(encode([1, 256, 0]), &mut dest),
+                LindelStatus::ValueOutOfRange
+            );
+            assert_eq!(dest, [1, 255, 0]);
+
+            // Widening can never lose bits.
+            let key = encode([u16::MAX, 7, 300]);
+            let mut wide = [0u64; 3];
+            let status = unsafe {
+                perform_decode_narrowing(
+                    encoding_type,
+                    16,
+                    64,
+                    &key as *const u64 as *const c_void,
+                    wide.as_mut_ptr() as *mut c_void,
+                    3,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            assert_eq!(wide, [u16::MAX as u64, 7, 300]);
+        }
+    }
+
+    #[test]
+    fn resolution_snaps_to_grid_cells() {
+        for encoding_type in 0..2u8 {
+            let encode = |values: [u32; 2], resolution_bits: u8| {
+                let mut key: u64 = 0;
+                let written = unsafe {
+                    lindel_encode_resolution(
+                        encoding_type,
+                        32,
+                        values.as_ptr() as *const c_void,
+                        2,
+                        resolution_bits,
+                        &mut key as *mut u64 as *mut c_void,
+                    )
+                };
+                assert_eq!(written, 8);
+                key
+            };
+
+            // Both points are in the same 256 wide cell.
+            let a = [0x1234_5601, 0x0000_AB10];
+            let b = [0x1234_56FF, 0x0000_ABEE];
+            assert_eq!(encode(a, 8), encode(b, 8));
+            assert_ne!(encode(a, 0), encode(b, 0));
+            assert_ne!(encode(a, 4), encode(b, 4));
+            let mut expected: u64 = 0;
+            unsafe {
+                encode_values(
+                    encoding_type,
+                    32,
+                    a.as_ptr() as *const c_void,
+                    2,
+                    &mut expected as *mut u64 as *mut c_void,
+                );
+            }
+            assert_eq!(encode(a, 0), expected);
+
+            let key = encode(b, 8);
+            let mut corner = [0u32; 2];
+            let status = unsafe {
+                perform_decode_resolution(
+                    encoding_type,
+                    32,
+                    8,
+                    &key as *const u64 as *const c_void,
+                    corner.as_mut_ptr() as *mut c_void,
+                    2,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            assert_eq!(corner, [0x1234_5600, 0x0000_AB00]);
+
+            let mut key: u64 = 0;
+            let status = unsafe {
+                lindel_encode_resolution(
+                    encoding_type,
+                    32,
+                    a.as_ptr() as *const c_void,
+                    2,
+                    32,
+                    &mut key as *mut u64 as *mut c_void,
+                )
+            };
+            assert_eq!(status, LindelStatus::InvalidPrecision.into());
+        }
+    }
+
+    #[test]
+    fn encode_any_key_always_holds_every_bit() {
+        for encoding_type in 0..2u8 {
+            for width in [8u8, 16, 32, 64] {
+                for dims in 1..=16usize {
+                    let values = [0xFFu8; 16 * 8];
+                    let mut key = [0u8; 16];
+                    let written = unsafe {
+                        encode_any(
+                            encoding_type,
+                            width,
+                            values.as_ptr(),
+                            dims,
+                            key.as_mut_ptr() as *mut c_void,
+                        )
+                    };
+                    let used_bits = width as usize * dims;
+                    if used_bits > 128 {
+                        assert_eq!(written, LindelStatus::InvalidDimensions.into());
+                        continue;
+                    }
+
+                    // The smallest sufficient key, and a Morton key of all
+                    // ones values has exactly the bits of the values set.
+                    let written = written as usize;
+                    assert!(written * 8 >= used_bits);
+                    assert!(written == 1 || written * 4 < used_bits);
+                    if encoding_type == 1 {
+                        let key = unsafe { read_key(key.as_ptr() as *const c_void, written * 8) };
+                        assert_eq!(key.count_ones() as usize, used_bits);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hilbert_min_order_for_coordinate_maxima() {
+        assert_eq!(hilbert_min_order_2d(0, 0), 1);
+        assert_eq!(hilbert_min_order_2d(1, 1), 1);
+        assert_eq!(hilbert_min_order_2d(2, 1), 2);
+        assert_eq!(hilbert_min_order_2d(3, 255), 8);
+        assert_eq!(hilbert_min_order_2d(256, 0), 9);
+        assert_eq!(hilbert_min_order_2d(0, 1 << 30), 31);
+        assert_eq!(hilbert_min_order_2d(u32::MAX - 1, 5), 32);
+        assert_eq!(hilbert_min_order_2d(u32::MAX, u32::MAX), 32);
+
+        // The chosen order always holds both coordinates.
+        for (x, y) in [(5, 1000), (70_000, 3), (1 << 20, 1 << 19)] {
+            let order = hilbert_min_order_2d(x, y);
+            assert_ne!(hilbert_xy2d(order, x, y), u64::MAX);
+            assert_eq!(hilbert_xy2d(order - 1, x, y), u64::MAX);
+        }
+    }
+
+    #[test]
+    fn encode_ranks_uses_minimal_widths() {
+        let cardinalities = [1u32, 2, 3, 4, 5, 256, 257, 1000];
+        let ranks = [0u32, 1, 2, 3, 4, 255, 256, 999];
+        let mut key: u128 = 0;
+        let mut widths = [0u8; 8];
+        let written = unsafe {
+            encode_ranks(
+                ranks.as_ptr(),
+                cardinalities.as_ptr(),
+                8,
+                &mut key as *mut u128 as *mut c_void,
+                widths.as_mut_ptr(),
+            )
+        };
+        assert_eq!(widths, [1, 1, 2, 2, 3, 8, 9, 10]);
+        // 36 bits fit in a u64.
+        assert_eq!(written, 8);
+
+        let mut decoded = [0u64; 8];
+        let status = unsafe {
+            morton_decode_uneven(
+                widths.as_ptr(),
+                8,
+                &key as *const u128 as *const c_void,
+                decoded.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, LindelStatus::Ok);
+        assert_eq!(decoded, ranks.map(u64::from));
+
+        let mut encode = |ranks: [u32; 2], cardinalities: [u32; 2]| unsafe {
+            encode_ranks(
+                ranks.as_ptr(),
+                cardinalities.as_ptr(),
+                2,
+                &mut key as *mut u128 as *mut c_void,
+                widths.as_mut_ptr(),
+            )
+        };
+        assert_eq!(encode([3, 0], [3, 1]), LindelStatus::ValueOutOfRange.into());
+        assert_eq!(
+            encode([0, 0], [3, 0]),
+            LindelStatus::InvalidPrecision.into()
+        );
+        assert_eq!(encode([u32::MAX - 1, 0], [u32::MAX, 1]), 8);
+        assert_eq!(widths[..2], [32, 1]);
+    }
+
+    #[test]
+    fn decode_debug_formats_values() {
+        let debug = |encoding_type: u8, key: u64, dims: usize| unsafe {
+            let text =
+                perform_decode_debug(encoding_type, 16, dims, &key as *const u64 as *const c_void);
+            if text.is_null() {
+                return None;
+            }
+            let formatted = std::ffi::CStr::from_ptr(text).to_str().unwrap().to_owned();
+            duckdb_free(text as *mut c_void);
+            Some(formatted)
+        };
+
+        let key = lindel::morton_encode([1u16, 65535, 300]);
+        assert_eq!(debug(1, key, 3).as_deref(), Some("[1, 65535, 300]"));
+        let key = lindel::hilbert_encode([7u16, 0, 42]);
+        assert_eq!(debug(0, key, 3).as_deref(), Some("[7, 0, 42]"));
+        assert_eq!(debug(0, key, 9), None);
+        assert_eq!(debug(2, key, 3), None);
+    }
+
+    #[test]
+    fn supported_shapes_are_listed() {
+        let needed = unsafe { lindel_supported_shapes(std::ptr::null_mut(), 0) };
+        let mut out = vec![0u8; needed];
+        assert_eq!(
+            unsafe { lindel_supported_shapes(out.as_mut_ptr(), out.len()) },
+            needed
+        );
+        let shapes: Vec<(u8, u8)> = out.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+        assert_eq!(shapes, [(8, 16), (16, 8), (32, 4), (64, 2)]);
+        assert!(!shapes.iter().any(|&(width, _)| width == 12 || width == 128));
+
+        // Every listed shape decodes, and one more dimension doesn't.
+        let key = [0u128; 1];
+        let mut dest = [0u128; 2];
+        for (width, max_dims) in shapes {
+            let mut decode = |dims: usize| {
+                perform_decode(
+                    1,
+                    width,
+                    key.as_ptr() as *const c_void,
+                    dest.as_mut_ptr() as *mut c_void,
+                    dims,
+                )
+            };
+            assert_eq!(decode(max_dims as usize), LindelStatus::Ok);
+            assert_eq!(
+                decode(max_dims as usize + 1),
+                LindelStatus::InvalidDimensions
+            );
+        }
+
+        // Only whole pairs are written to a short buffer.
+        let mut short = [0xAAu8; 5];
+        assert_eq!(
+            unsafe { lindel_supported_shapes(short.as_mut_ptr(), 5) },
+            needed
+        );
+        assert_eq!(short, [8, 16, 16, 8, 0xAA]);
+    }
+
+    #[test]
+    fn index_valued_elements_round_trip_in_every_family() {
+        // Element `i` is `i + 1`, so an arm that reads or writes the wrong
+        // element, like the `args[16]` bug, decodes to the wrong value.
+        for element_bit_width in [8u8, 16, 32, 64] {
+            for dims in 1..=128 / element_bit_width as usize {
+                let mut values = [0u128; 1];
+                let values = values.as_mut_ptr() as *mut c_void;
+                for i in 0..dims {
+                    unsafe { write_lane(values, element_bit_width, i, i as u64 + 1) };
+                }
+                let bits = key_bits(element_bit_width, dims).unwrap();
+
+                macro_rules! typed {
+                    ($var:ident, $blob:ident, $type:ty) => {{
+                        let mut key = [0u128; 1];
+                        let key_ptr = key.as_mut_ptr() as *mut c_void;
+                        let written = unsafe { $var(values as *const $type, dims, key_ptr, false) };
+                        assert_eq!(written as usize * 8, bits);
+                        let mut blob = [0u8; 16];
+                        assert_eq!(
+                            unsafe { $blob(values as *const $type, dims, blob.as_mut_ptr()) },
+                            16
+                        );
+                        assert_eq!(u128::from_be_bytes(blob), unsafe {
+                            read_key(key_ptr, bits)
+                        });
+                        key[0]
+                    }};
+                }
+                let keys = match element_bit_width {
+                    8 => [
+                        typed!(hilbert_encode_u8_var, hilbert_encode_u8_var_blob, u8),
+                        typed!(morton_encode_u8_var, morton_encode_u8_var_blob, u8),
+                    ],
+                    16 => [
+                        typed!(hilbert_encode_u16_var, hilbert_encode_u16_var_blob, u16),
+                        typed!(morton_encode_u16_var, morton_encode_u16_var_blob, u16),
+                    ],
+                    32 => [
+                        typed!(hilbert_encode_u32_var, hilbert_encode_u32_var_blob, u32),
+                        typed!(morton_encode_u32_var, morton_encode_u32_var_blob, u32),
+                    ],
+                    _ => [
+                        typed!(hilbert_encode_u64_var, hilbert_encode_u64_var_blob, u64),
+                        typed!(morton_encode_u64_var, morton_encode_u64_var_blob, u64),
+                    ],
+                };
+
+                for (encoding_type, key) in keys.iter().enumerate() {
+                    let mut decoded = [0u128; 1];
+                    let decoded = decoded.as_mut_ptr() as *mut c_void;
+                    let status = perform_decode(
+                        encoding_type as u8,
+                        element_bit_width,
+                        key as *const u128 as *const c_void,
+                        decoded,
+                        dims,
+                    );
+                    assert_eq!(status, LindelStatus::Ok);
+                    for i in 0..dims {
+                        assert_eq!(
+                            unsafe { read_lane(decoded, element_bit_width, i) },
+                            i as u64 + 1,
+                            "encoding {} width {} dims {}",
+                            encoding_type,
+                            element_bit_width,
+                            dims
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reversed_dimension_order_round_trip() {
+        let yx: [u16; 3] = [0x00FF, 0x1234, 0xF00D];
+        let xy: [u16; 3] = [0xF00D, 0x1234, 0x00FF];
+        for encoding_type in 0..2u8 {
+            let encode = |values: &[u16; 3], reverse_dims: bool| {
+                let mut key: u64 = 0;
+                let written = unsafe {
+                    lindel_encode_dim_order(
+                        encoding_type,
+                        16,
+                        values.as_ptr() as *const c_void,
+                        3,
+                        reverse_dims,
+                        &mut key as *mut u64 as *mut c_void,
+                    )
+                };
+                assert_eq!(written, 8);
+                key
+            };
+
+            // Reversing matches encoding the manually reversed list.
+            assert_eq!(encode(&yx, true), encode(&xy, false));
+            assert_ne!(encode(&yx, true), encode(&yx, false));
+
+            for reverse_dims in [false, true] {
+                let key = encode(&yx, reverse_dims);
+                let mut decoded = [0u16; 3];
+                let status = unsafe {
+                    perform_decode_dim_order(
+                        encoding_type,
+                        16,
+                        reverse_dims,
+                        &key as *const u64 as *const c_void,
+                        decoded.as_mut_ptr() as *mut c_void,
+                        3,
+                    )
+                };
+                assert_eq!(status, LindelStatus::Ok);
+                assert_eq!(decoded, yx);
+            }
+        }
+    }
+
+    #[test]
+    fn hilbert_prefix_range_spans_the_cell() {
+        let range = |prefix: u128, significant_bits: u8, total_bits: u8| {
+            let (mut min, mut max) = (0u128, 0u128);
+            let status = unsafe {
+                hilbert_prefix_range(
+                    &prefix as *const u128 as *const c_void,
+                    significant_bits,
+                    total_bits,
+                    &mut min as *mut u128 as *mut c_void,
+                    &mut max as *mut u128 as *mut c_void,
+                )
+            };
+            (status, min, max)
+        };
+
+        // Two u16 dimensions, the top 8 bits of the key select a cell that is
+        // 4096 wide in each dimension.
+        let key = lindel::hilbert_encode([0x1234u16, 0xABCD]);
+        let prefix = (key >> 24) as u128;
+        let (status, min, max) = range(prefix, 8, 32);
+        assert_eq!(status, LindelStatus::Ok);
+        assert_eq!(max - min + 1, 1 << 24);
+        assert!(min <= key as u128 && key as u128 <= max);
+
+        let mut state: u64 = 0xBB67_AE85_84CA_A73B;
+        let keys = [min, max]
+            .into_iter()
+            .chain((0..200).map(|_| min + (xorshift(&mut state) as u128 & 0xFF_FFFF)));
+        for key in keys {
+            let [x, y]: [u16; 2] = lindel::hilbert_decode(key as u32);
+            assert_eq!((x >> 12, y >> 12), (0x1, 0xA));
+        }
+
+        assert_eq!(range(0, 0, 128), (LindelStatus::Ok, 0, u128::MAX));
+        assert_eq!(
+            range(u128::MAX, 128, 128),
+            (LindelStatus::Ok, u128::MAX, u128::MAX)
+        );
+        assert_eq!(range(0x100, 8, 32).0, LindelStatus::KeyOutOfRange);
+        assert_eq!(range(0, 33, 32).0, LindelStatus::InvalidPrecision);
+        assert_eq!(range(0, 0, 129).0, LindelStatus::InvalidPrecision);
+    }
+
+    #[test]
+    fn subset_decode_writes_selected_dimensions() {
+        let values: [u32; 4] = [11, 22, 33, 44];
+        for encoding_type in 0..2u8 {
+            let mut key: u128 = 0;
+            unsafe {
+                encode_values(
+                    encoding_type,
+                    32,
+                    values.as_ptr() as *const c_void,
+                    4,
+                    &mut key as *mut u128 as *mut c_void,
+                );
+            }
+            let subset = |dim_mask: u32| {
+                let mut dest = [u32::MAX; 4];
+                let status = unsafe {
+                    perform_decode_subset(
+                        encoding_type,
+                        32,
+                        4,
+                        dim_mask,
+                        &key as *const u128 as *const c_void,
+                        dest.as_mut_ptr() as *mut c_void,
+                    )
+                };
+                (status, dest)
+            };
+
+            assert_eq!(
+                subset(0b0101),
+                (LindelStatus::Ok, [11, 33, u32::MAX, u32::MAX])
+            );
+            assert_eq!(
+                subset(0b1000),
+                (LindelStatus::Ok, [44, u32::MAX, u32::MAX, u32::MAX])
+            );
+            assert_eq!(subset(0b1111), (LindelStatus::Ok, values));
+            assert_eq!(subset(0), (LindelStatus::Ok, [u32::MAX; 4]));
+            assert_eq!(subset(0b10001).0, LindelStatus::InvalidDimensions);
+        }
+    }
+
+    #[test]
+    fn uuid_dimension_orders_like_duckdb() {
+        let uuids = [
+            "00000000-0000-0000-0000-000000000000",
+            "00000000-0000-0000-0000-000000000001",
+            "47183823-2574-4bfd-b411-99ed177d3e43",
+            "7fffffff-ffff-ffff-ffff-ffffffffffff",
+            "80000000-0000-0000-0000-000000000000",
+            "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+            "ffffffff-ffff-ffff-ffff-ffffffffffff",
+        ];
+        // DuckDB's hugeint representation, with the top bit of `upper`
+        // flipped so the signed comparison orders UUIDs by their text.
+        let hugeints: Vec<(u64, u64)> = uuids
+            .iter()
+            .map(|uuid| {
+                let bits = u128::from_str_radix(&uuid.replace('-', ""), 16).unwrap();
+                (((bits >> 64) as u64) ^ 1 << 63, bits as u64)
+            })
+            .collect();
+        assert!(hugeints
+            .windows(2)
+            .all(|pair| ((pair[0].0 as i64), pair[0].1) < ((pair[1].0 as i64), pair[1].1)));
+
+        let mut values = Vec::new();
+        for &(hi, lo) in &hugeints {
+            let mut value: u128 = 0;
+            let status = unsafe { encode_uuid_dim(hi, lo, &mut value as *mut u128 as *mut c_void) };
+            assert_eq!(status, LindelStatus::Ok);
+            values.push(value);
+
+            let (mut decoded_hi, mut decoded_lo) = (0, 0);
+            let status = unsafe {
+                decode_uuid_dim(
+                    &value as *const u128 as *const c_void,
+                    &mut decoded_hi,
+                    &mut decoded_lo,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            assert_eq!((decoded_hi, decoded_lo), (hi, lo));
+        }
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(values[6], u128::MAX);
+        assert_eq!(values[4], 1 << 127);
+    }
+
+    #[test]
+    fn hugeint_keys_order_like_unsigned_keys() {
+        // Two u64 values whose Morton keys straddle 2^127.
+        let below: [u64; 2] = [0x7FFF_FFFF_FFFF_FFFF, u64::MAX];
+        let above: [u64; 2] = [0x8000_0000_0000_0000, 0];
+        assert!(lindel::morton_encode(below) < 1 << 127);
+        assert!(lindel::morton_encode(above) >= 1 << 127);
+
+        let hugeint = |encoding_type: u8, width: u8, values: *const c_void, dims: usize| {
+            let mut key: i128 = 0;
+            let written = unsafe {
+                lindel_encode_hugeint(
+                    encoding_type,
+                    width,
+                    values,
+                    dims,
+                    &mut key as *mut i128 as *mut c_void,
+                )
+            };
+            assert_eq!(written, 16);
+            key
+        };
+
+        let low = hugeint(1, 64, below.as_ptr() as *const c_void, 2);
+        let high = hugeint(1, 64, above.as_ptr() as *const c_void, 2);
+        assert!(low < high);
+        assert!((lindel::morton_encode(below) as i128) > (lindel::morton_encode(above) as i128));
+
+        for (key, values) in [(low, below), (high, above)] {
+            let mut decoded = [0u64; 2];
+            let status = unsafe {
+                perform_decode_hugeint(
+                    1,
+                    64,
+                    &key as *const i128 as *const c_void,
+                    decoded.as_mut_ptr() as *mut c_void,
+                    2,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            assert_eq!(decoded, values);
+        }
+
+        // Narrower keys keep their order too.
+        let mut state: u64 = 0x5BE0_CD19_137E_2179;
+        let mut pairs: Vec<(u64, i128)> = (0..200)
+            .map(|_| {
+                let values = [xorshift(&mut state) as u16, xorshift(&mut state) as u16, 7];
+                let key = lindel::hilbert_encode(values);
+                (key, hugeint(0, 16, values.as_ptr() as *const c_void, 3))
+            })
+            .collect();
+        pairs.sort();
+        assert!(pairs.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+
+        let out_of_range: i128 = (1 << 48) ^ i128::MIN;
+        let mut decoded = [0u16; 3];
+        let status = unsafe {
+            perform_decode_hugeint(
+                0,
+                16,
+                &out_of_range as *const i128 as *const c_void,
+                decoded.as_mut_ptr() as *mut c_void,
+                3,
+            )
+        };
+        assert_eq!(status, LindelStatus::KeyOutOfRange);
+    }
+
+    #[test]
+    fn hilbert_3d_with_explicit_order() {
+        let voxel = |order: u8, d: u128| {
+            let (mut x, mut y, mut z) = (0, 0, 0);
+            let status = unsafe {
+                hilbert_d2xyz(
+                    order,
+                    &d as *const u128 as *const c_void,
+                    &mut x,
+                    &mut y,
+                    &mut z,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            [x, y, z]
+        };
+        let index = |order: u8, [x, y, z]: [u32; 3]| {
+            let mut d: u128 = 0;
+            let status =
+                unsafe { hilbert_xyz2d(order, x, y, z, &mut d as *mut u128 as *mut c_void) };
+            (status, d)
+        };
+
+        // Every voxel of the 8x8x8 curve round trips, and each step moves to
+        // a face adjacent voxel.
+        let mut seen = std::collections::HashSet::new();
+        let mut previous: Option<[u32; 3]> = None;
+        for d in 0..512u128 {
+            let cell = voxel(3, d);
+            assert!(cell.iter().all(|&value| value < 8));
+            assert!(seen.insert(cell));
+            assert_eq!(index(3, cell), (LindelStatus::Ok, d));
+            if let Some(previous) = previous {
+                let distance: u32 = previous.iter().zip(cell).map(|(a, b)| a.abs_diff(b)).sum();
+                assert_eq!(distance, 1);
+            }
+            previous = Some(cell);
+        }
+        assert_eq!(voxel(3, 0), [0, 0, 0]);
+
+        // The full order matches lindel.
+        let values = [0xDEAD_BEEF, 7, u32::MAX];
+        assert_eq!(
+            index(32, values),
+            (LindelStatus::Ok, lindel::hilbert_encode(values))
+        );
+        assert_eq!(voxel(32, lindel::hilbert_encode(values)), values);
+
+        assert_eq!(index(3, [8, 0, 0]).0, LindelStatus::ValueOutOfRange);
+        assert_eq!(index(0, [0, 0, 0]).0, LindelStatus::InvalidPrecision);
+        let (mut x, mut y, mut z) = (0, 0, 0);
+        let d: u128 = 512;
+        let status = unsafe {
+            hilbert_d2xyz(
+                3,
+                &d as *const u128 as *const c_void,
+                &mut x,
+                &mut y,
+                &mut z,
+            )
+        };
+        assert_eq!(status, LindelStatus::ValueOutOfRange);
+    }
+
+    #[test]
+    fn cell_count_covers_every_key() {
+        let cell_count = |dims: u8, bits_per_dim: u8| {
+            let mut out: u128 = 0;
+            let status = unsafe {
+                lindel_cell_count(dims, bits_per_dim, &mut out as *mut u128 as *mut c_void)
+            };
+            (status, out)
+        };
+
+        assert_eq!(cell_count(2, 2), (LindelStatus::Ok, 16));
+        assert_eq!(cell_count(3, 8), (LindelStatus::Ok, 1 << 24));
+        assert_eq!(cell_count(1, 64), (LindelStatus::Ok, 1 << 64));
+        assert_eq!(cell_count(9, 14), (LindelStatus::Ok, 1 << 126));
+        assert_eq!(cell_count(127, 1), (LindelStatus::Ok, 1 << 127));
+
+        // The largest key of a shape is one less than its cell count.
+        let values = [u16::MAX; 3];
+        let mut key: u64 = 0;
+        unsafe {
+            hilbert_encode_u16_var(
+                values.as_ptr(),
+                3,
+                &mut key as *mut u64 as *mut c_void,
+                false,
+            )
+        };
+        assert!((key as u128) < cell_count(3, 16).1);
+
+        // 2^128 cells doesn't fit in a u128.
+        assert_eq!(cell_count(2, 64).0, LindelStatus::ValueOutOfRange);
+        assert_eq!(cell_count(128, 1).0, LindelStatus::ValueOutOfRange);
+        assert_eq!(cell_count(255, 255).0, LindelStatus::ValueOutOfRange);
+        assert_eq!(cell_count(0, 8).0, LindelStatus::InvalidDimensions);
+        assert_eq!(cell_count(2, 0).0, LindelStatus::InvalidPrecision);
+    }
+
+    #[test]
+    fn morton_spread_builds_morton_keys() {
+        let spread = |value: u64, dims: u8, bits: u8| {
+            let mut out: u128 = 0;
+            let status =
+                unsafe { morton_spread(value, dims, bits, &mut out as *mut u128 as *mut c_void) };
+            assert_eq!(status, LindelStatus::Ok);
+            out
+        };
+        let compact = |spread: u128, dims: u8, bits: u8| {
+            let mut out: u64 = 0;
+            let status = unsafe {
+                morton_compact(
+                    &spread as *const u128 as *const c_void,
+                    dims,
+                    bits,
+                    &mut out,
+                )
+            };
+            assert_eq!(status, LindelStatus::Ok);
+            out
+        };
+
+        assert_eq!(spread(0b1011, 2, 4), 0b1000101);
+        assert_eq!(spread(0b11, 3, 2), 0b1001);
+
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        for _ in 0..1000 {
+            let r = xorshift(&mut state);
+            let [x, y] = [r as u32, (r >> 32) as u32];
+            let key = lindel::morton_encode([x, y]) as u128;
+            assert_eq!(spread(x as u64, 2, 32) << 1 | spread(y as u64, 2, 32), key);
+            assert_eq!(compact(key >> 1, 2, 32), x as u64);
+            assert_eq!(compact(key, 2, 32), y as u64);
+
+            let values = [r as u16, (r >> 16) as u16, (r >> 32) as u16];
+            let key = (0..3)
+                .map(|d| spread(values[d] as u64, 3, 16) << (2 - d))
+                .fold(0, |key, part| key | part);
+            assert_eq!(key, lindel::morton_encode(values) as u128);
+            for (d, &value) in values.iter().enumerate() {
+                assert_eq!(compact(key >> (2 - d), 3, 16), value as u64);
+            }
+
+            assert_eq!(compact(spread(r, 2, 64), 2, 64), r);
+        }
+
+        let mut out: u128 = 0;
+        let out = &mut out as *mut u128 as *mut c_void;
+        unsafe {
+            assert_eq!(morton_spread(16, 2, 4, out), LindelStatus::ValueOutOfRange);
+            assert_eq!(
+                morton_spread(1, 3, 64, out),
+                LindelStatus::InvalidDimensions
+            );
+            assert_eq!(morton_spread(1, 0, 8, out), LindelStatus::InvalidDimensions);
+            assert_eq!(morton_spread(1, 2, 0, out), LindelStatus::InvalidPrecision);
+        }
+    }
+
+    #[test]
+    fn offset_keys_round_trip() {
+        let encode = |encoding_type: u8, width: u8, values: &[i64], offsets: &[i64]| {
+            let mut key: u128 = 0;
+            let written = unsafe {
+                lindel_encode_offset(
+                    encoding_type,
+                    width,
+                    values.as_ptr(),
+                    offsets.as_ptr(),
+                    values.len(),
+                    &mut key as *mut u128 as *mut c_void,
+                )
+            };
+            (written, key)
+        };
+        let decode = |encoding_type: u8, width: u8, key: u128, offsets: &[i64]| {
+            let mut values = vec![0i64; offsets.len()];
+            let status = unsafe {
+                perform_decode_offset(
+                    encoding_type,
+                    width,
+                    offsets.as_ptr(),
+                    &key as *const u128 as *const c_void,
+                    values.as_mut_ptr(),
+                    offsets.len(),
+                )
+            };
+            (status, values)
+        };
+
+        let offsets = [0, 1000, -250];
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        for _ in 0..500 {
+            let r = xorshift(&mut state);
+            let values = [
+                r as i16 as i64,
+                1000 + (r >> 16) as i16 as i64,
+                -250 + (r >> 32) as i16 as i64,
+            ];
+            for encoding_type in 0..=1 {
+                let (written, key) = encode(encoding_type, 16, &values, &offsets);
+                assert_eq!(written, 8);
+                assert_eq!(
+                    decode(encoding_type, 16, key, &offsets),
+                    (LindelStatus::Ok, values.to_vec())
+                );
+            }
+        }
+
+        // The origin of each dimension encodes like the middle of the range,
+        // so a value at its offset sorts between those on either side of it.
+        let (_, origin) = encode(1, 8, &[1000, -7], &[1000, -7]);
+        assert_eq!(origin, lindel::morton_encode([0x80u8, 0x80]) as u128);
+        let (_, below) = encode(1, 8, &[999, -8], &[1000, -7]);
+        let (_, above) = encode(1, 8, &[1001, -6], &[1000, -7]);
+        assert!(below < origin && origin < above);
+
+        // Values at the edge of the range around the offset.
+        let offsets = [100, -100];
+        let edges = [100 - 128, -100 + 127];
+        let (_, key) = encode(0, 8, &edges, &offsets);
+        assert_eq!(
+            decode(0, 8, key, &offsets),
+            (LindelStatus::Ok, edges.to_vec())
+        );
+        assert_eq!(
+            encode(0, 8, &[100 - 129, 0], &offsets).0,
+            LindelStatus::ValueOutOfRange as i32
+        );
+        assert_eq!(
+            encode(0, 8, &[0, -100 + 128], &offsets).0,
+            LindelStatus::ValueOutOfRange as i32
+        );
+
+        // At 64 bits an offset can push the decoded values out of an i64.
+        let offsets = [i64::MAX, i64::MIN];
+        let edges = [i64::MAX, i64::MIN];
+        let (written, key) = encode(0, 64, &edges, &offsets);
+        assert_eq!(written, 16);
+        assert_eq!(
+            decode(0, 64, key, &offsets),
+            (LindelStatus::Ok, edges.to_vec())
+        );
+        assert_eq!(
+            encode(1, 64, &[i64::MIN, 0], &[1, 0]).0,
+            LindelStatus::ValueOutOfRange as i32
+        );
+        let (_, key) = encode(1, 64, &[1, -1], &[0, 0]);
+        assert_eq!(
+            decode(1, 64, key, &[i64::MAX, 0]).0,
+            LindelStatus::ValueOutOfRange
+        );
+        assert_eq!(
+            decode(1, 64, key, &[0, i64::MIN]).0,
+            LindelStatus::ValueOutOfRange
+        );
+    }
+
+    #[test]
+    fn hi_lo_words_rebuild_the_key() {
+        let hi_lo = |encoding_type: u8, width: u8, values: *const u8, dims: usize| {
+            let (mut hi, mut lo) = (0u64, 0u64);
+            let written =
+                unsafe { encode_hi_lo(encoding_type, width, values, dims, &mut hi, &mut lo) };
+            (written, (hi as u128) << 64 | lo as u128)
+        };
+
+        let mut state = 0xD1B5_4A32_D192_ED03;
+        for _ in 0..500 {
+            let r = xorshift(&mut state);
+            let s = xorshift(&mut state);
+
+            let values = [r, s];
+            assert_eq!(
+                hi_lo(0, 64, values.as_ptr() as *const u8, 2),
+                (16, lindel::hilbert_encode(values))
+            );
+            assert_eq!(
+                hi_lo(1, 64, values.as_ptr() as *const u8, 2),
+                (16, lindel::morton_encode(values))
+            );
+
+            let values = [r as u32, (r >> 32) as u32, s as u32, (s >> 32) as u32];
+            assert_eq!(
+                hi_lo(0, 32, values.as_ptr() as *const u8, 4),
+                (16, lindel::hilbert_encode(values))
+            );
+
+            let values = [r as u32, (r >> 32) as u32, s as u32];
+            assert_eq!(
+                hi_lo(1, 32, values.as_ptr() as *const u8, 3),
+                (16, lindel::morton_encode(values))
+            );
+
+            // Narrower keys are zero extended.
+            let values = [r as u16, (r >> 16) as u16];
+            assert_eq!(
+                hi_lo(0, 16, values.as_ptr() as *const u8, 2),
+                (4, lindel::hilbert_encode(values) as u128)
+            );
+        }
+
+        let values = [0u64; 3];
+        assert_eq!(
+            hi_lo(0, 64, values.as_ptr() as *const u8, 3).0,
+            LindelStatus::InvalidDimensions as i32
+        );
+    }
+
+    #[test]
+    fn centered_decode_returns_signed_coordinates() {
+        let offsets = [0i64; 2];
+        for (width, values) in [
+            (8u8, [-5i64, 127]),
+            (16, [-5, -32768]),
+            (32, [-5, 1]),
+            (64, [-5, i64::MIN]),
+        ] {
+            for encoding_type in 0..=1 {
+                let mut key: u128 = 0;
+                let key_ptr = &mut key as *mut u128 as *mut c_void;
+                let written = unsafe {
+                    lindel_encode_offset(
+                        encoding_type,
+                        width,
+                        values.as_ptr(),
+                        offsets.as_ptr(),
+                        2,
+                        key_ptr,
+                    )
+                };
+                assert!(written > 0);
+
+                let mut decoded = [0u64; 2];
+                let decode = |centered: bool, decoded: &mut [u64; 2]| unsafe {
+                    perform_decode_typed(
+                        encoding_type,
+                        width,
+                        1,
+                        centered,
+                        key_ptr,
+                        decoded.as_mut_ptr() as *mut c_void,
+                        2,
+                    )
+                };
+                assert_eq!(decode(true, &mut decoded), LindelStatus::Ok);
+                let centered: Vec<i64> = (0..2)
+                    .map(|i| unsafe { read_lane(decoded.as_ptr() as *const c_void, width, i) })
+                    .map(|value| ((value << (64 - width)) as i64) >> (64 - width))
+                    .collect();
+                assert_eq!(centered, values);
+
+                // Without centering the flipped sign bit makes -5 a large
+                // positive value.
+                assert_eq!(decode(false, &mut decoded), LindelStatus::Ok);
+                let raw = unsafe { read_lane(decoded.as_ptr() as *const c_void, width, 0) };
+                assert_eq!(raw, (1 << (width - 1)) - 5);
+            }
+        }
+
+        let key: u64 = 0;
+        let mut dest = [0u32; 2];
+        let mut centered = |logical_type| unsafe {
+            perform_decode_typed(
+                0,
+                32,
+                logical_type,
+                true,
+                &key as *const u64 as *const c_void,
+                dest.as_mut_ptr() as *mut c_void,
+                2,
+            )
+        };
+        assert_eq!(centered(1), LindelStatus::Ok);
+        assert_eq!(centered(0), LindelStatus::InvalidLogicalType);
+        assert_eq!(centered(2), LindelStatus::InvalidLogicalType);
+        assert_eq!(dest, [i32::MIN as u32; 2]);
+
+        let key_ptr = &key as *const u64 as *const c_void;
+        let dest_ptr = dest.as_mut_ptr() as *mut c_void;
+        for centered in [false, true] {
+            let typed =
+                |src, dest| unsafe { perform_decode_typed(0, 32, 1, centered, src, dest, 2) };
+            assert_eq!(typed(std::ptr::null(), dest_ptr), LindelStatus::NullPointer);
+            assert_eq!(
+                typed(key_ptr, std::ptr::null_mut()),
+                LindelStatus::NullPointer
+            );
+        }
+    }
+
+    #[test]
+    fn infer_width_from_a_sample_of_keys() {
+        let infer = |keys: &[u128], dims: u8| unsafe {
+            lindel_infer_width(keys.as_ptr() as *const c_void, keys.len(), dims)
+        };
+
+        let mut state = 0xA076_1D64_78BD_642F;
+        let keys: Vec<u128> = (0..64)
+            .map(|_| {
+                let r = xorshift(&mut state);
+                let values = [r as u16, (r >> 16) as u16, (r >> 32) as u16];
+                lindel::hilbert_encode(values) as u128
+            })
+            .collect();
+        assert_eq!(infer(&keys, 3), 16);
+        assert_eq!(infer(&keys, 6), 8);
+
+        let keys: Vec<u128> = (0..64)
+            .map(|_| {
+                let r = xorshift(&mut state);
+                lindel::morton_encode([r as u32, (r >> 32) as u32, r.rotate_left(7) as u32])
+            })
+            .collect();
+        assert_eq!(infer(&keys, 3), 32);
+
+        // Small values can't be told apart from a narrower encoding.
+        let key = lindel::morton_encode([200u16, 3, 1]) as u128;
+        assert_eq!(infer(&[key], 3), 8);
+        assert_eq!(infer(&[], 3), 8);
+
+        assert_eq!(infer(&[u128::MAX], 3), LindelStatus::KeyOutOfRange as i32);
+        assert_eq!(infer(&[1 << 70], 1), LindelStatus::KeyOutOfRange as i32);
+        assert_eq!(infer(&[1], 0), LindelStatus::InvalidDimensions as i32);
+    }
+
+    #[test]
+    fn fixed_binary_rows_sort_like_keys() {
+        const ROWS: usize = 200;
+        let mut buffer = vec![0u8; ROWS * 16];
+        let mut keys = Vec::with_capacity(ROWS);
+        let mut state = 0x5851_F42D_4C95_7F2D;
+        for row in 0..ROWS {
+            let r = xorshift(&mut state);
+            let values = [r as u32, (r >> 32) as u32, r.rotate_left(17) as u32];
+            let written = unsafe {
+                encode_fixed_binary(
+                    0,
+                    32,
+                    values.as_ptr() as *const u8,
+                    3,
+                    row,
+                    buffer.as_mut_ptr(),
+                    buffer.len(),
+                )
+            };
+            assert_eq!(written, 16);
+            keys.push(lindel::hilbert_encode(values));
+        }
+
+        let rows: Vec<&[u8]> = buffer.chunks_exact(16).collect();
+        for (row, key) in rows.iter().zip(&keys) {
+            assert_eq!(u128::from_be_bytes((*row).try_into().unwrap()), *key);
+        }
+        let mut by_bytes: Vec<usize> = (0..ROWS).collect();
+        by_bytes.sort_by_key(|&i| rows[i]);
+        let mut by_key: Vec<usize> = (0..ROWS).collect();
+        by_key.sort_by_key(|&i| keys[i]);
+        assert_eq!(by_bytes, by_key);
+
+        // Narrower keys are zero extended into the row.
+        let values = [0xABu8, 0xCD];
+        let written =
+            unsafe { encode_fixed_binary(1, 8, values.as_ptr(), 2, 1, buffer.as_mut_ptr(), 32) };
+        assert_eq!(written, 16);
+        assert_eq!(
+            u128::from_be_bytes(buffer[16..32].try_into().unwrap()),
+            lindel::morton_encode(values) as u128
+        );
+        assert_eq!(buffer[..16], keys[0].to_be_bytes());
+
+        let status =
+            unsafe { encode_fixed_binary(1, 8, values.as_ptr(), 2, 2, buffer.as_mut_ptr(), 32) };
+        assert_eq!(status, LindelStatus::BufferTooSmall as i32);
+        let status = unsafe {
+            encode_fixed_binary(
+                1,
+                8,
+                values.as_ptr(),
+                2,
+                usize::MAX,
+                buffer.as_mut_ptr(),
+                32,
+            )
+        };
+        assert_eq!(status, LindelStatus::BufferTooSmall as i32);
+    }
+
+    #[test]
+    fn tagged_keys_reject_a_different_shape() {
+        let encode = |encoding_type: u8, width: u8, values: *const u8, dims: usize| {
+            let mut tagged = [0u8; 17];
+            let written = unsafe {
+                lindel_encode_tagged(encoding_type, width, values, dims, tagged.as_mut_ptr(), 17)
+            };
+            assert!(written > 0);
+            tagged[..written as usize].to_vec()
+        };
+        let decode = |encoding_type: u8, width: u8, tagged: &[u8], dest: &mut [u32]| unsafe {
+            perform_decode_tagged(
+                encoding_type,
+                width,
+                tagged.as_ptr(),
+                tagged.len(),
+                dest.as_mut_ptr() as *mut c_void,
+                dest.len(),
+            )
+        };
+
+        // The values are small, so the plain key has no bits set above 96
+        // and a 3-D decode of it isn't detected.
+        let values: [u32; 4] = [7, 0x00AD_BEEF, 12, 3];
+        let mut plain: u128 = 0;
+        unsafe {
+            morton_encode_u32_var(
+                values.as_ptr(),
+                4,
+                &mut plain as *mut u128 as *mut c_void,
+                false,
+            );
+        }
+        let mut three = [0u32; 3];
+        let status = unsafe {
+            perform_decode_checked(
+                1,
+                32,
+                &plain as *const u128 as *const c_void,
+                three.as_mut_ptr() as *mut c_void,
+                3,
+            )
+        };
+        assert_eq!(status, LindelStatus::Ok);
+
+        for encoding_type in 0..=1 {
+            let tagged = encode(encoding_type, 32, values.as_ptr() as *const u8, 4);
+            assert_eq!(tagged.len(), 17);
+
+            let mut three = [0u32; 3];
+            assert_eq!(
+                decode(encoding_type, 32, &tagged, &mut three),
+                LindelStatus::ShapeMismatch
+            );
+            assert_eq!(three, [0; 3]);
+
+            let mut four = [0u32; 4];
+            assert_eq!(
+                decode(encoding_type, 32, &tagged, &mut four),
+                LindelStatus::Ok
+            );
+            assert_eq!(four, values);
+
+            // A different encoding type or element width is rejected too,
+            // as is a truncated key.
+            assert_eq!(
+                decode(1 - encoding_type, 32, &tagged, &mut four),
+                LindelStatus::ShapeMismatch
+            );
+            let mut eight = [0u32; 4];
+            assert_eq!(
+                decode(encoding_type, 16, &tagged, &mut eight[..]),
+                LindelStatus::ShapeMismatch
+            );
+            assert_eq!(
+                decode(encoding_type, 32, &tagged[..16], &mut four),
+                LindelStatus::ShapeMismatch
+            );
+        }
+
+        // Every shape round trips through its own tag.
+        let mut state = 0x243F_6A88_85A3_08D3;
+        for width in [8u8, 16, 32, 64] {
+            for dims in 1..=128 / width as usize {
+                let values: Vec<u8> = (0..16).map(|_| xorshift(&mut state) as u8).collect();
+                let tagged = encode(1, width, values.as_ptr(), dims);
+                let mut decoded = [0u128; 1];
+                let status = unsafe {
+                    perform_decode_tagged(
+                        1,
+                        width,
+                        tagged.as_ptr(),
+                        tagged.len(),
+                        decoded.as_mut_ptr() as *mut c_void,
+                        dims,
+                    )
+                };
+                assert_eq!(status, LindelStatus::Ok);
+                let bytes = dims * width as usize / 8;
+                assert_eq!(decoded[0].to_ne_bytes()[..bytes], values[..bytes]);
+            }
+        }
+
+        let mut small = [0u8; 16];
+        let status = unsafe {
+            lindel_encode_tagged(
+                0,
+                32,
+                values.as_ptr() as *const u8,
+                4,
+                small.as_mut_ptr(),
+                16,
+            )
+        };
+        assert_eq!(status, LindelStatus::BufferTooSmall as i32);
+        let status =
+            unsafe { lindel_encode_tagged(0, 32, std::ptr::null(), 4, small.as_mut_ptr(), 16) };
+        assert_eq!(status, LindelStatus::NullPointer as i32);
+    }
+
+    // A small xorshift generator so the property tests are repeatable without
+    // pulling in a dependency.
+    fn xorshift(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn morton_u16_three_dimensions_is_z_ordered() {
+        // True if the most significant set bit of `a` is below that of `b`.
+        fn less_msb(a: u16, b: u16) -> bool {
+            a < b && a < (a ^ b)
+        }
+
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        let mut keyed: Vec<(u64, [u16; 3])> = (0..5000)
+            .map(|_| {
+                let r = xorshift(&mut state);
+                let values = [r as u16, (r >> 16) as u16, (r >> 32) as u16];
+                let mut key: u64 = 0;
+                unsafe {
+                    morton_encode_u16_var(
+                        values.as_ptr(),
+                        3,
+                        &mut key as *mut u64 as *mut c_void,
+                        false,
+                    );
+                }
+                assert_eq!(key >> 48, 0);
+                (key, values)
+            })
+            .collect();
+        keyed.sort();
+
+        for pair in keyed.windows(2) {
+            let ((key_a, a), (key_b, b)) = (pair[0], pair[1]);
+            if key_a == key_b {
+                assert_eq!(a, b);
+                continue;
+            }
+            // The dimension with the most significant differing bit decides the
+            // order, on a tie the earlier dimension wins since it is interleaved
+            // into the higher bit.
+            let mut deciding = 0;
+            for dim in 1..3 {
+                if less_msb(a[deciding] ^ b[deciding], a[dim] ^ b[dim]) {
+                    deciding = dim;
+                }
+            }
+            assert!(a[deciding] < b[deciding], "{:?} sorted before {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn big_endian_keys_sort_byte_wise() {
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        let mut keyed: Vec<(u32, [u8; 4])> = (0..2000)
+            .map(|_| {
+                let r = xorshift(&mut state);
+                let values = [r as u16, (r >> 16) as u16];
+                let mut key: u32 = 0;
+                let mut stored: u32 = 0;
+                unsafe {
+                    hilbert_encode_u16_var(
+                        values.as_ptr(),
+                        2,
+                        &mut key as *mut u32 as *mut c_void,
+                        false,
+                    );
+                    hilbert_encode_u16_var(
+                        values.as_ptr(),
+                        2,
+                        &mut stored as *mut u32 as *mut c_void,
+                        true,
+                    );
+                }
+                let bytes = stored.to_ne_bytes();
+                assert_eq!(bytes, key.to_be_bytes());
+                (key, bytes)
+            })
+            .collect();
+
+        keyed.sort_by_key(|(key, _)| *key);
+        let by_key = keyed.clone();
+        keyed.sort_by_key(|(_, bytes)| *bytes);
+        assert_eq!(by_key, keyed);
+    }
+
+    #[test]
+    fn describe_key_dimension_counts() {
+        let describe = |element_bit_width, key: u128, src_bits| unsafe {
+            lindel_describe_key(
+                0,
+                element_bit_width,
+                &key as *const u128 as *const c_void,
+                src_bits,
+            )
+        };
+
+        assert_eq!(describe(16, 1000, 64), (1 << 3) | (1 << 4));
+        assert_eq!(describe(16, 1 << 50, 64), 1 << 4);
+        assert_eq!(describe(16, 1000, 32), 1 << 2);
+        assert_eq!(describe(8, 1000, 32), (1 << 3) | (1 << 4));
+        assert_eq!(
+            describe(8, 1 << 100, 128),
+            (1 << 13) | (1 << 14) | (1 << 15) | (1 << 16)
+        );
+        assert_eq!(describe(64, 1, 128), 1 << 2);
+        assert_eq!(describe(64, 1, 32), 0);
+        assert_eq!(describe(24, 1, 32), 0);
+        assert_eq!(describe(16, 1, 48), 0);
+    }
+
+    #[test]
+    fn hilbert_u64_bits_round_trip() {
+        let values: [u64; 3] = [0xFF_FFFF_FFFF, 0x12_3456_789A, 7];
+        let mut key: u128 = 0;
+        let status = unsafe {
+            hilbert_encode_u64_bits(values.as_ptr(), 3, 40, &mut key as *mut u128 as *mut c_void)
+        };
+        assert_eq!(status, LindelStatus::Ok);
+        assert!(key < 1 << 120);
+
+        let mut decoded = [0u64; 3];
+        let status = unsafe {
+            hilbert_decode_u64_bits(
+                &key as *const u128 as *const c_void,
+                3,
+                40,
+                decoded.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, LindelStatus::Ok);
+        assert_eq!(decoded, values);
+
+        let too_wide: [u64; 3] = [1 << 40, 0, 0];
+        let status = unsafe {
+            hilbert_encode_u64_bits(
+                too_wide.as_ptr(),
+                3,
+                40,
+                &mut key as *mut u128 as *mut c_void,
+            )
+        };
+        assert_eq!(status, LindelStatus::ValueOutOfRange);
+        let status = unsafe {
+            hilbert_encode_u64_bits(values.as_ptr(), 4, 40, &mut key as *mut u128 as *mut c_void)
+        };
+        assert_eq!(status, LindelStatus::InvalidDimensions);
+        let status = unsafe {
+            hilbert_encode_u64_bits(values.as_ptr(), 3, 0, &mut key as *mut u128 as *mut c_void)
+        };
+        assert_eq!(status, LindelStatus::InvalidPrecision);
+
+        // Smaller shapes are stored in a smaller key.
+        let mut small_key: u64 = 0;
+        let status = unsafe {
+            hilbert_encode_u64_bits(
+                [5u64, 9].as_ptr(),
+                2,
+                20,
+                &mut small_key as *mut u64 as *mut c_void,
+            )
+        };
+        assert_eq!(status, LindelStatus::Ok);
+        let mut decoded = [0u64; 2];
+        unsafe {
+            hilbert_decode_u64_bits(
+                &small_key as *const u64 as *const c_void,
+                2,
+                20,
+                decoded.as_mut_ptr(),
+            );
+        }
+        assert_eq!(decoded, [5, 9]);
+    }
+
+    #[test]
+    fn panics_are_converted_to_a_status() {
+        assert_eq!(
+            ffi_boundary::<LindelStatus, _>(|| panic!("deliberate")),
+            LindelStatus::Panic
+        );
+        assert_eq!(ffi_boundary(|| LindelStatus::Ok), LindelStatus::Ok);
+
+        // A null pointer panics inside the encoder, the caller should see an
+        // error rather than the process aborting.
+        let mut result: u128 = 0;
+        let status = unsafe {
+            hilbert_encode_u64_var(
+                std::ptr::null(),
+                2,
+                &mut result as *mut u128 as *mut c_void,
+                false,
+            )
+        };
+        assert_eq!(status, LindelStatus::Panic.into());
+    }
+
+    #[test]
+    fn encode_rejects_lengths_beyond_isize_max() {
+        let values = [1u64, 2];
+        let mut result = [0u128; 1];
+        let out = result.as_mut_ptr() as *mut c_void;
+        let expected = i32::from(LindelStatus::InvalidDimensions);
+        let too_long = isize::MAX as usize + 1;
+        unsafe {
+            for len in [too_long, usize::MAX] {
+                assert_eq!(
+                    hilbert_encode_u8_var(values.as_ptr() as *const u8, len, out, false),
+                    expected
+                );
+                assert_eq!(
+                    morton_encode_u8_var(values.as_ptr() as *const u8, len, out, false),
+                    expected
+                );
+            }
+
+            // The byte length of the wider types overflows before the
+            // element count does.
+            for len in [too_long / 2, usize::MAX] {
+                assert_eq!(
+                    hilbert_encode_u16_var(values.as_ptr() as *const u16, len, out, false),
+                    expected
+                );
+                assert_eq!(
+                    morton_encode_u64_var(values.as_ptr(), len, out, false),
+                    expected
+                );
+            }
+            assert_eq!(
+                hilbert_encode_u32_var(values.as_ptr() as *const u32, too_long / 4, out, false),
+                expected
+            );
+            assert_eq!(
+                morton_encode_u64_var(values.as_ptr(), too_long / 8, out, false),
+                expected
+            );
+            assert_eq!(
+                hilbert_encode_u64_var_blob(values.as_ptr(), usize::MAX, out as *mut u8),
+                expected
+            );
+            assert_eq!(
+                hilbert_encode_u64_bits(values.as_ptr(), usize::MAX, 64, out),
+                LindelStatus::InvalidDimensions
+            );
+        }
+        assert_eq!(result, [0]);
+    }
+
+    #[test]
+    fn checked_decode_rejects_overlapping_buffers() {
+        let values: [u16; 4] = [1, 2, 3, 4];
+        let mut scratch = [0u64; 2];
+        unsafe {
+            hilbert_encode_u16_var(
+                values.as_ptr(),
+                4,
+                scratch.as_mut_ptr() as *mut c_void,
+                false,
+            );
+        }
+
+        // Decoding in place, and into a region that starts part way through
+        // the key must both be refused.
+        let base = scratch.as_mut_ptr() as *mut u8;
+        for offset in [0usize, 4] {
+            let status = unsafe {
+                perform_decode_checked(
+                    0,
+                    16,
+                    base as *const c_void,
+                    base.add(offset) as *mut c_void,
+                    4,
+                )
+            };
+            assert_eq!(status, LindelStatus::OverlappingBuffers);
+        }
+
+        // Directly after the key is fine.
+        let status = unsafe {
+            perform_decode_checked(0, 16, base as *const c_void, base.add(8) as *mut c_void, 4)
+        };
+        assert_eq!(status, LindelStatus::Ok);
+        let decoded: [u16; 4] = unsafe { std::ptr::read_unaligned(base.add(8) as *const [u16; 4]) };
+        assert_eq!(decoded, values);
+    }
+}
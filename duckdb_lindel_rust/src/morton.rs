@@ -0,0 +1,96 @@
+// duckdb_lindel_rust
+// Copyright 2024 Rusty Conover <rusty@conover.me>
+// Licensed under the MIT License
+
+// Table driven Morton decoding for two and three dimensions.
+//
+// Rather than extracting the key a bit at a time, each byte of the key is
+// looked up in a table that holds the bits it contributes to every
+// dimension.  With three dimensions a byte doesn't start on a group boundary,
+// so there is a table for each of the three phases a byte can start at.
+// `bench::morton_table_decode` compares this with lindel's decode.
+//
+// As with lindel, the first dimension occupies the most significant bit of
+// each group of interleaved bits.
+
+// Build the table for bytes whose lowest bit is at position `phase` within a
+// group of `dims` interleaved bits.  Entry `[byte][dim]` holds the bits of
+// `dim` in the byte, relative to the first value bit the byte covers.
+const fn table(dims: usize, phase: usize) -> [[u8; 3]; 256] {
+    let mut table = [[0u8; 3]; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut bit = 0;
+        while bit < 8 {
+            if byte >> bit & 1 == 1 {
+                let position = phase + bit;
+                table[byte][dims - 1 - position % dims] |= 1 << (position / dims);
+            }
+            bit += 1;
+        }
+        byte += 1;
+    }
+    table
+}
+
+static TABLES_2D: [[[u8; 3]; 256]; 1] = [table(2, 0)];
+static TABLES_3D: [[[u8; 3]; 256]; 3] = [table(3, 0), table(3, 1), table(3, 2)];
+
+// Decode a Morton key of `D` values of `bits` bits each, or None if there are
+// no tables for `D` dimensions.
+pub(crate) fn decode<const D: usize>(key: u128, bits: u32) -> Option<[u64; D]> {
+    let tables: &[[[u8; 3]; 256]] = match D {
+        2 => &TABLES_2D,
+        3 => &TABLES_3D,
+        _ => return None,
+    };
+    let mut values = [0u64; D];
+    for index in 0..(D * bits as usize).div_ceil(8) {
+        let start = 8 * index;
+        let entry = &tables[start % D][(key >> start) as u8 as usize];
+        for (value, bits) in values.iter_mut().zip(entry) {
+            *value |= (*bits as u64) << (start / D);
+        }
+    }
+    Some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_lindel() {
+        let mut state: u64 = 0x3C6E_F372_FE94_F82B;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            state
+        };
+
+        for _ in 0..2000 {
+            let r = next() as u128 | (next() as u128) << 64;
+
+            let expected = lindel::morton_decode::<u8, 2>(r as u16).map(u64::from);
+            assert_eq!(decode::<2>(r as u16 as u128, 8), Some(expected));
+            let expected = lindel::morton_decode::<u16, 2>(r as u32).map(u64::from);
+            assert_eq!(decode::<2>(r as u32 as u128, 16), Some(expected));
+            let expected = lindel::morton_decode::<u32, 2>(r as u64).map(u64::from);
+            assert_eq!(decode::<2>(r as u64 as u128, 32), Some(expected));
+            let expected = lindel::morton_decode::<u64, 2>(r);
+            assert_eq!(decode::<2>(r, 64), Some(expected));
+
+            let key = r as u32 & 0xFF_FFFF;
+            let expected = lindel::morton_decode::<u8, 3>(key).map(u64::from);
+            assert_eq!(decode::<3>(key as u128, 8), Some(expected));
+            let key = r as u64 & 0xFFFF_FFFF_FFFF;
+            let expected = lindel::morton_decode::<u16, 3>(key).map(u64::from);
+            assert_eq!(decode::<3>(key as u128, 16), Some(expected));
+            let key = r & ((1 << 96) - 1);
+            let expected = lindel::morton_decode::<u32, 3>(key).map(u64::from);
+            assert_eq!(decode::<3>(key, 32), Some(expected));
+        }
+        assert_eq!(decode::<4>(0, 8), None);
+    }
+}
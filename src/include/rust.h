@@ -6,9 +6,78 @@
 #include <new>
 
 
+/// Byte order for `encode_from_bytes()` of values stored big endian.
+static const uint8_t LINDEL_BIG_ENDIAN = 1;
+
+/// Byte order for `encode_from_bytes()` of values stored little endian.
+static const uint8_t LINDEL_LITTLE_ENDIAN = 0;
+
+/// The most keys that `hilbert_nearby_keys()` returns.
+static const size_t LINDEL_MAX_NEARBY_KEYS = 1024;
+
+/// Flag for the float encoders to reject positive and negative infinity.
+static const uint8_t LINDEL_REJECT_INFINITE = 2;
+
+/// Flag for the float encoders to reject NaN values rather than encode them.
+static const uint8_t LINDEL_REJECT_NAN = 1;
+
+/// Status codes returned by the checked entry points.
+enum class LindelStatus : int32_t {
+  Ok = 0,
+  InvalidEncodingType = -1,
+  InvalidElementBitWidth = -2,
+  InvalidDimensions = -3,
+  /// The key has significant bits beyond what the requested shape can hold.
+  KeyOutOfRange = -4,
+  /// The source and destination buffers overlap.
+  OverlappingBuffers = -5,
+  /// An unexpected panic was caught before it could unwind into DuckDB.
+  Panic = -6,
+  /// The requested number of significant bits isn't valid for the shape.
+  InvalidPrecision = -7,
+  /// An input value has bits set beyond the requested precision.
+  ValueOutOfRange = -8,
+  /// The logical type isn't known or doesn't exist at the element width.
+  InvalidLogicalType = -9,
+  /// A floating point value is NaN or infinite and was asked to be rejected.
+  NonFiniteValue = -10,
+  /// The destination is too small for the key.
+  BufferTooSmall = -11,
+  /// A bit transform matrix isn't invertible.
+  SingularMatrix = -12,
+  /// The byte order is neither little nor big endian.
+  InvalidByteOrder = -13,
+  /// A required pointer argument is null.
+  NullPointer = -14,
+  /// A tagged key was encoded with a different shape than was requested.
+  ShapeMismatch = -15,
+};
+
+/// The type of the column a key was encoded from, which `perform_decode_typed()`
+/// validates against the element width.
+enum class LindelLogicalType : uint8_t {
+  Unsigned = 0,
+  /// Two's complement signed integers of the element width.
+  Signed = 1,
+  /// IEEE 754 floats, only valid for 32 and 64 bit elements.
+  Float = 2,
+};
+
+/// A signed 128 bit integer laid out like DuckDB's `hugeint_t`, since C has
+/// no 128 bit integer type to pass across the FFI boundary.
+struct LindelHugeint {
+  uint64_t lower;
+  int64_t upper;
+};
+
 
 extern "C" {
 
+/// Convert a dimension value produced by `encode_uuid_dim()` back into the
+/// `upper` and `lower` halves of DuckDB's `hugeint_t` representation of the
+/// UUID.
+LindelStatus decode_uuid_dim(const void *src, uint64_t *hi_out, uint64_t *lo_out);
+
 ///Free a value returned from `duckdb_malloc`, `duckdb_value_varchar`, `duckdb_value_blob`, or `duckdb_value_string`.
 ///
 /// ptr: The memory region to de-allocate.
@@ -19,26 +88,942 @@ extern void duckdb_free(void *ptr);
 /// size: The number of bytes to allocate.  returns: A pointer to the allocated memory region.
 extern void *duckdb_malloc(size_t size);
 
-void hilbert_encode_u16_var(const uint16_t *ptr, size_t len, void *result);
+/// Encode `dims` values of `element_bit_width` bits, a single entry point for
+/// every element width rather than picking one of the typed encoders.
+///
+/// `values` points to the raw bytes of the values in native byte order.
+/// Neither `values` nor `result` needs to be aligned.
+///
+/// The key is always stored in the smallest of 8, 16, 32, 64 or 128 bits that
+/// holds all `dims * element_bit_width` bits, so no bits are ever lost, and
+/// `InvalidDimensions` is returned if they need more than 128 bits, for which
+/// `morton_encode_wide()` can be used instead.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t encode_any(uint8_t encoding_type,
+                   uint8_t element_bit_width,
+                   const uint8_t *values,
+                   size_t dims,
+                   void *result);
+
+/// Encode like `encode_any()` into row `row` of a packed buffer of 16 byte
+/// keys, the layout of an Arrow `FixedSizeBinary(16)` array, so a batch can be
+/// exported without a copy or an allocation per row.
+///
+/// The key is widened to 128 bits and stored big-endian at byte
+/// `row * 16` of `out`, so the bytes of the keys sort in the same order as the
+/// keys themselves.  `out_len` is the size of the whole buffer in bytes.
+///
+/// Returns the number of bytes written, always 16, or a negative
+/// `LindelStatus` on error.  `BufferTooSmall` is returned if the row doesn't
+/// fit within `out_len` bytes.
+int32_t encode_fixed_binary(uint8_t encoding_type,
+                            uint8_t element_bit_width,
+                            const uint8_t *values,
+                            size_t dims,
+                            size_t row,
+                            uint8_t *out,
+                            size_t out_len);
+
+/// Encode `dims` values of `element_bit_width` bits read from raw bytes in
+/// the given `byte_order`, either `LINDEL_LITTLE_ENDIAN` or
+/// `LINDEL_BIG_ENDIAN`, e.g. coordinates from a BLOB in an external format.
+///
+/// Neither `src` nor `result` needs to be aligned, and the key is stored in
+/// native byte order like the other encoders.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t encode_from_bytes(uint8_t encoding_type,
+                          uint8_t element_bit_width,
+                          size_t dims,
+                          uint8_t byte_order,
+                          const uint8_t *src,
+                          void *result);
+
+/// Encode like `encode_any()` but return the key as its high and low 64 bits
+/// through `hi` and `lo`, so a binding can assemble a 128 bit key without
+/// passing a u128 across the FFI boundary.  Keys narrower than 128 bits are
+/// zero extended, so `hi` is zero for them.
+///
+/// Returns the number of bytes in the key, or a negative `LindelStatus` on
+/// error, in which case neither `hi` nor `lo` is written.
+int32_t encode_hi_lo(uint8_t encoding_type,
+                     uint8_t element_bit_width,
+                     const uint8_t *values,
+                     size_t dims,
+                     uint64_t *hi,
+                     uint64_t *lo);
+
+/// Morton encode dictionary encoded ranks with `morton_encode_uneven()`, using
+/// the fewest bits for each dimension that hold every rank below its
+/// cardinality, `ceil(log2(cardinality))` but at least one.
+///
+/// The chosen width of each dimension is written to `width_out`, which takes
+/// `dims` bytes and is what `morton_decode_uneven()` needs to recover the
+/// ranks.  A cardinality of zero returns `InvalidPrecision` and a rank that
+/// isn't below its cardinality returns `ValueOutOfRange`.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t encode_ranks(const uint32_t *ranks,
+                     const uint32_t *cardinalities,
+                     size_t dims,
+                     void *result,
+                     uint8_t *width_out);
+
+/// Convert a DuckDB UUID to an unsigned 128 bit dimension value that orders
+/// exactly as DuckDB orders UUIDs, so it can be encoded as one dimension.
+///
+/// DuckDB stores a UUID as a `hugeint_t` whose `upper` half has its top bit
+/// flipped, so that the signed comparison of the hugeint sorts UUIDs by their
+/// text.  `hi` is the bits of `upper` and `lo` is `lower`, and flipping the
+/// bit back gives the 128 bits of the UUID, whose unsigned order is the same.
+/// The value is written to `result` as an unaligned u128.
+LindelStatus encode_uuid_dim(uint64_t hi, uint64_t lo, void *result);
+
+/// Check whether two Hilbert keys of `dims` values of `element_bit_width`
+/// bits are the keys of spatially adjacent cells, which differ by exactly one
+/// in exactly one dimension.
+///
+/// Returns 1 if the cells are adjacent, 0 if they aren't, including when they
+/// are the same cell, or a negative `LindelStatus` on error.
+int32_t hilbert_are_adjacent(const void *key_a,
+                             const void *key_b,
+                             size_t dims,
+                             uint8_t element_bit_width);
+
+/// Compute the bounding box of the Hilbert cell that a key represents at a
+/// precision of `level_bits` interleaved bits.
+///
+/// The lower corner is written to `min_out` and the upper, inclusive, corner
+/// to `max_out`, both as `dims` values of `element_bit_width` bits.  A cell is
+/// `2^(element_bit_width - level_bits / dims)` wide in every dimension, so
+/// `level_bits` must be a multiple of `dims`.
+///
+/// Returns `NullPointer` if `key`, `min_out` or `max_out` is null.
+LindelStatus hilbert_cell_bounds(const void *key,
+                                 uint8_t element_bit_width,
+                                 size_t dims,
+                                 uint8_t level_bits,
+                                 void *min_out,
+                                 void *max_out);
+
+/// Compute the cell at index `d` along the classic 2-D Hilbert curve of side
+/// `2^order`, the inverse of `hilbert_xy2d()`.
+///
+/// Returns `InvalidPrecision` if `order` isn't between 1 and 31 and
+/// `ValueOutOfRange` if `d` isn't below `4^order`.
+LindelStatus hilbert_d2xy(uint8_t order, uint64_t d, uint32_t *x_out, uint32_t *y_out);
+
+/// Compute the voxel at the index that `d` points to along the 3-D Hilbert
+/// curve of side `2^order`, the inverse of `hilbert_xyz2d()`.
+///
+/// Returns `InvalidPrecision` if `order` isn't between 1 and 32 and
+/// `ValueOutOfRange` if the index isn't below `8^order`.
+LindelStatus hilbert_d2xyz(uint8_t order,
+                           const void *d,
+                           uint32_t *x_out,
+                           uint32_t *y_out,
+                           uint32_t *z_out);
+
+/// Decode a key produced by `hilbert_encode_decimal_var()` back into the
+/// scaled integers of `dims` DECIMAL values, written to `dest` which doesn't
+/// need to be aligned.
+LindelStatus hilbert_decode_decimal(const void *src,
+                                    uint8_t scale,
+                                    size_t dims,
+                                    LindelHugeint *dest);
+
+/// Decode a key produced by `hilbert_encode_u64_bits()` with the same number
+/// of dimensions and bits per dimension into `dims` u64 values.
+LindelStatus hilbert_decode_u64_bits(const void *src,
+                                     size_t dims,
+                                     uint8_t bits_per_dim,
+                                     uint64_t *dest);
+
+/// Compute the distance along the Hilbert curve between two points of `dims`
+/// values of `element_bit_width` bits, the absolute difference of their
+/// indices, which avoids overflowing a signed subtraction of 128 bit keys.
+///
+/// The distance is written to `result` as an unaligned u128.
+///
+/// Returns `Ok` as zero, or a negative `LindelStatus` on error.
+int32_t hilbert_distance(const void *values_a,
+                         const void *values_b,
+                         size_t dims,
+                         uint8_t element_bit_width,
+                         void *result);
+
+/// Hilbert encode up to two DECIMAL values from their underlying scaled
+/// integers, so they are ordered numerically without converting to floats.
+///
+/// `values` points to one `LindelHugeint` per dimension, which doesn't need to
+/// be aligned, and each value is mapped to an unsigned integer by flipping its
+/// sign bit so negative values come before positive ones.  A single value
+/// uses the full 128 bits, so every DECIMAL(38) fits.  Two values share the
+/// 128 bit key, so each must fit in 64 bits as it does for a DECIMAL of up to
+/// 18 digits, and `ValueOutOfRange` is returned for wider values.  The key is
+/// always 16 bytes.
+///
+/// `scale` is only metadata, the values of every dimension must share it for
+/// the keys to be comparable, and it returns `InvalidPrecision` if it's
+/// beyond the 38 digits of a DECIMAL.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_decimal_var(const LindelHugeint *values,
+                                   uint8_t scale,
+                                   size_t dims,
+                                   void *result);
+
+/// `float_checks` is a combination of `LINDEL_REJECT_NAN` and
+/// `LINDEL_REJECT_INFINITE`, a rejected value returns `NonFiniteValue`.
+/// Any other NaN is encoded as the standard quiet NaN.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_f32_var(const float *ptr,
+                               size_t len,
+                               void *result,
+                               bool big_endian,
+                               uint8_t float_checks);
+
+/// `float_checks` is a combination of `LINDEL_REJECT_NAN` and
+/// `LINDEL_REJECT_INFINITE`, a rejected value returns `NonFiniteValue`.
+/// Any other NaN is encoded as the standard quiet NaN.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_f64_var(const double *ptr,
+                               size_t len,
+                               void *result,
+                               bool big_endian,
+                               uint8_t float_checks);
+
+/// Hilbert encode `row_count` rows of `dims` u32 values stored column by
+/// column, as DuckDB vectors are, so a whole chunk can be encoded without
+/// transposing it into rows first.
+///
+/// Value `j` of row `i` is read from `dim_ptrs[j][i]`.  The keys are written
+/// one after another to `result` in native byte order, each of the size that
+/// `hilbert_encode_u32_var()` writes for `dims` values, and `result` doesn't
+/// need to be aligned.  The shape is validated once for the whole chunk.
+///
+/// Returns the number of bytes written for each key, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_soa(const uint32_t *const *dim_ptrs,
+                           size_t dims,
+                           size_t row_count,
+                           void *result);
+
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_u16_var(const uint16_t *ptr, size_t len, void *result, bool big_endian);
+
+/// Returns 16, the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_u16_var_blob(const uint16_t *ptr, size_t len, uint8_t *result);
+
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_u32_var(const uint32_t *ptr, size_t len, void *result, bool big_endian);
+
+/// Returns 16, the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_u32_var_blob(const uint32_t *ptr, size_t len, uint8_t *result);
+
+/// Hilbert encode `dims` u64 values of which only the low `bits_per_dim` bits
+/// are meaningful, so for example three 40 bit values fit in a u128.  The key
+/// is for a curve of order `bits_per_dim` and is stored in the smallest
+/// unsigned integer that holds `dims * bits_per_dim` bits.
+///
+/// Values with bits set above `bits_per_dim` are rejected rather than masked.
+LindelStatus hilbert_encode_u64_bits(const uint64_t *values,
+                                     size_t dims,
+                                     uint8_t bits_per_dim,
+                                     void *result);
+
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_u64_var(const uint64_t *ptr, size_t len, void *result, bool big_endian);
+
+/// Returns 16, the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_u64_var_blob(const uint64_t *ptr, size_t len, uint8_t *result);
+
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_u8_var(const uint8_t *ptr, size_t len, void *result, bool big_endian);
+
+/// Returns 16, the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t hilbert_encode_u8_var_blob(const uint8_t *ptr, size_t len, uint8_t *result);
+
+/// Determine if a Hilbert key is covered by a set of key ranges.
+///
+/// `ranges` points to `n_ranges` pairs of inclusive `[start, end]` keys which
+/// must be sorted by start and not overlap.  Returns 1 if the key falls within
+/// one of the ranges, 0 if it doesn't, or a negative `LindelStatus` on error.
+int32_t hilbert_in_ranges(uint64_t key, const uint64_t *ranges, size_t n_ranges);
+
+/// The minimum number of bits per dimension that represents every coordinate
+/// up to `max_x` and `max_y` without loss, between 1 and 32, e.g. to choose
+/// the `order` of `hilbert_xy2d()` or the element width to encode with.
+uint8_t hilbert_min_order_2d(uint32_t max_x, uint32_t max_y);
+
+/// Find up to `k` Hilbert keys near the cell that contains a point, to probe
+/// an index for approximate nearest neighbors.
+///
+/// `values` points to `dims` values of `element_bit_width` bits whose key must
+/// fit in a u64.  The point's own key is written to `out` first, followed by
+/// the keys of the cells that share a face with it, then the keys after and
+/// before it along the curve, without duplicates.  At most
+/// `LINDEL_MAX_NEARBY_KEYS` keys are written whatever `k` is.  Returns the number of
+/// keys written, which is zero if the shape is invalid.
+size_t hilbert_nearby_keys(const void *values,
+                           size_t dims,
+                           uint8_t element_bit_width,
+                           size_t k,
+                           uint64_t *out);
+
+/// Compute the inclusive range of full precision keys of `total_bits` bits
+/// that share the `significant_bits` prefix in `prefix_key`, for range scans
+/// at a coarser precision.
+///
+/// Every key with the same prefix lies within the one cell and the curve
+/// visits the cell contiguously, so the range is `prefix << cleared` to
+/// `(prefix << cleared) | (2^cleared - 1)` where `cleared` is
+/// `total_bits - significant_bits`.  `prefix_key` holds the prefix in its low
+/// `significant_bits` bits, and the keys and prefix are unaligned u128s.
+LindelStatus hilbert_prefix_range(const void *prefix_key,
+                                  uint8_t significant_bits,
+                                  uint8_t total_bits,
+                                  void *min_out,
+                                  void *max_out);
+
+/// Compute the index of the cell `(x, y)` along the classic 2-D Hilbert curve
+/// of side `2^order`, for 2-D map tiling with an explicit order.
+///
+/// This is the well known xy2d() algorithm, its curve starts at `(0, 0)` and
+/// ends at `(2^order - 1, 0)`, which is the same curve as the keys from
+/// `hilbert_encode_u64_bits()` of `[x, y]` with `order` bits per dimension.
+/// `order` must be between 1 and 31 and the coordinates below `2^order`,
+/// otherwise `u64::MAX` is returned which is never a valid index.
+uint64_t hilbert_xy2d(uint8_t order, uint32_t x, uint32_t y);
+
+/// Compute the index of the voxel `(x, y, z)` along the 3-D Hilbert curve of
+/// side `2^order`, for volumetric indexing with an explicit order.
+///
+/// This is John Skilling's algorithm, the same curve as the keys from
+/// `hilbert_encode_u64_bits()` of `[x, y, z]` with `order` bits per
+/// dimension.  The index is written to `result` as an unaligned u128.
+///
+/// Returns `InvalidPrecision` if `order` isn't between 1 and 32 and
+/// `ValueOutOfRange` if a coordinate isn't below `2^order`.
+LindelStatus hilbert_xyz2d(uint8_t order, uint32_t x, uint32_t y, uint32_t z, void *result);
+
+/// The number of distinct cells on a curve of `dims` dimensions with
+/// `bits_per_dim` bits each, `2^(dims * bits_per_dim)`, written to `out` as an
+/// unaligned u128.  Dividing a key by it normalizes the key to `[0, 1)`.
+///
+/// Returns `InvalidDimensions` for zero dimensions, `InvalidPrecision` for
+/// zero bits per dimension and `ValueOutOfRange` if the count doesn't fit a
+/// u128, which is the case for 128 or more bits in total.
+LindelStatus lindel_cell_count(uint8_t dims, uint8_t bits_per_dim, void *out);
+
+/// Describe which dimension counts could have produced a key, to help debug
+/// a decode that doesn't return the values that were encoded.
+///
+/// `src` points to a key stored in an integer of `src_bits` bits.  Bit `n` of
+/// the result is set if an encode of `n` values of `element_bit_width` bits
+/// produces a key of that width with no bits set beyond the values, for
+/// example a 64 bit key of 16 bit elements returns `(1 << 3) | (1 << 4)`
+/// unless its top 16 bits are set.  The result is zero if no dimension count
+/// fits, including for an invalid encoding type or width.
+uint32_t lindel_describe_key(uint8_t encoding_type,
+                             uint8_t element_bit_width,
+                             const void *src,
+                             uint8_t src_bits);
+
+/// Encode `dims` values of `element_bit_width` bits after reversing the order
+/// of the bits of each dimension `i` that has bit `i` of `reverse_mask` set.
+///
+/// Reversing a dimension makes its least significant bits dominate locality
+/// along that axis instead of its most significant bits.  A `reverse_mask` of
+/// zero encodes exactly like the encoder for the element width.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t lindel_encode_bit_reversed(uint8_t encoding_type,
+                                   uint8_t element_bit_width,
+                                   const void *values,
+                                   size_t dims,
+                                   uint32_t reverse_mask,
+                                   void *result);
+
+/// Encode `dims` values of `element_bit_width` bits into a destination that
+/// can hold `result_cap` bytes.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.  If the key is wider than `result_cap` bytes
+/// nothing is written and `BufferTooSmall` is returned.
+int32_t lindel_encode_checked(uint8_t encoding_type,
+                              uint8_t element_bit_width,
+                              const void *values,
+                              size_t dims,
+                              void *result,
+                              size_t result_cap);
+
+/// Encode `dims` values of `element_bit_width` bits, taking them in reverse
+/// order when `reverse_dims` is set, e.g. so a `[y, x]` list produces the same
+/// key as the `[x, y]` list from another source.
+///
+/// With `reverse_dims` clear this encodes exactly like the encoder for the
+/// element width.  `values` doesn't need to be aligned.
+///
+/// This reorders whole dimensions, `lindel_encode_bit_reversed()` reverses the
+/// bits within them instead.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t lindel_encode_dim_order(uint8_t encoding_type,
+                                uint8_t element_bit_width,
+                                const void *values,
+                                size_t dims,
+                                bool reverse_dims,
+                                void *result);
+
+/// Encode `dims` values of `element_bit_width` bits into a DuckDB HUGEINT,
+/// a signed i128, that orders the same as the unsigned key.
+///
+/// The key is widened to 128 bits and its top bit flipped, so keys at or
+/// above `2^127`, which would be negative as a plain i128, still sort after
+/// the keys below it.  Every key is flipped whatever its width, so keys of
+/// the same shape always compare correctly.  `result` receives 16 bytes and
+/// doesn't need to be aligned.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t lindel_encode_hugeint(uint8_t encoding_type,
+                              uint8_t element_bit_width,
+                              const void *values,
+                              size_t dims,
+                              void *result);
+
+/// Encode `dims` signed values relative to a per-dimension origin, so
+/// dimensions centered on different points share one curve.
+///
+/// Each value has its offset from `offsets` subtracted and is then stored as a
+/// signed integer of `element_bit_width` bits with its sign bit flipped, so
+/// `offset - 2^(w-1)` encodes as zero and the keys order like the values.
+/// Both `values` and `offsets` are `dims` i64s.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error, `ValueOutOfRange` if a value isn't within
+/// `[offset - 2^(w-1), offset + 2^(w-1) - 1]`.
+int32_t lindel_encode_offset(uint8_t encoding_type,
+                             uint8_t element_bit_width,
+                             const int64_t *values,
+                             const int64_t *offsets,
+                             size_t dims,
+                             void *result);
+
+/// Encode `dims` values of `element_bit_width` bits snapped to a grid of
+/// cells `2^resolution_bits` wide, by shifting each value right by
+/// `resolution_bits` before interleaving, for level of detail indexing.
+///
+/// Every point within the same cell produces the same key, and a
+/// `resolution_bits` of zero encodes exactly like the encoder for the element
+/// width.  `resolution_bits` must be less than `element_bit_width`, otherwise
+/// `InvalidPrecision` is returned.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t lindel_encode_resolution(uint8_t encoding_type,
+                                 uint8_t element_bit_width,
+                                 const void *values,
+                                 size_t dims,
+                                 uint8_t resolution_bits,
+                                 void *result);
+
+/// Encode `dims` values of `element_bit_width` bits like `encode_any()`, but
+/// prefix the key with a byte that records the encoding type, element width
+/// and number of dimensions, so `perform_decode_tagged()` can reject a decode
+/// of a different shape.
+///
+/// The plain keys of e.g. three and four u32 values are both stored in a
+/// u128, so a decode of the wrong number of dimensions can't always be
+/// detected from the key alone.  The tagged key is the tag byte followed by
+/// the key in native byte order, `1 + key bytes` in all, and `result` doesn't
+/// need to be aligned.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.  `BufferTooSmall` is returned if `result_len` is
+/// less than the size of the tagged key.
+int32_t lindel_encode_tagged(uint8_t encoding_type,
+                             uint8_t element_bit_width,
+                             const uint8_t *values,
+                             size_t dims,
+                             uint8_t *result,
+                             size_t result_len);
+
+/// Encode `dims` values of `element_bit_width` bits after transforming the
+/// bits of each value by an invertible matrix over GF(2), which generalizes
+/// bit reversal and Gray coding into any linear reshuffling of the bits.
+///
+/// `matrices` points to `dims` matrices of `element_bit_width` rows each, one
+/// per dimension.  Bit `i` of a transformed value is the parity of the bits
+/// of the value selected by row `i`, so the identity matrix has row `i` equal
+/// to `1 << i`.  A singular matrix is rejected with `SingularMatrix`.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t lindel_encode_transformed(uint8_t encoding_type,
+                                  uint8_t element_bit_width,
+                                  const void *values,
+                                  size_t dims,
+                                  const uint64_t *matrices,
+                                  void *result);
+
+/// Guess the `element_bit_width` that a sample of `n_keys` keys of `dims`
+/// dimensions was encoded with, for columns whose encoding wasn't recorded.
+///
+/// `keys` points to the keys as unaligned u128s.  The highest set bit across
+/// the sample is spread over the dimensions and rounded up to the next of 8,
+/// 16, 32 or 64 bits.  This is only a heuristic, a sample whose values are
+/// all small can't be told apart from a narrower encoding, so the larger the
+/// sample the better.
+///
+/// Returns the element width, or a negative `LindelStatus` if `dims` is zero
+/// or a key has more bits set than any width of `dims` values can produce.
+int32_t lindel_infer_width(const void *keys, size_t n_keys, uint8_t dims);
+
+/// Encode `dims` values of `element_bit_width` bits and immediately decode the
+/// key again, as a self-test that the shape round trips.
+///
+/// `values` points to the raw bytes of the values in native byte order and
+/// doesn't need to be aligned.
+///
+/// Returns 1 if the decoded values exactly match `values` and 0 if they don't,
+/// or if the encoding type, width or number of dimensions isn't supported.
+int32_t lindel_roundtrip_check(uint8_t encoding_type,
+                               uint8_t element_bit_width,
+                               const void *values,
+                               size_t dims);
+
+/// Describe every supported encode and decode shape as pairs of
+/// `(element_bit_width, max_dims)` bytes, so the SQL layer can validate its
+/// inputs before calling and produce a friendly error.  Every number of
+/// dimensions from 1 to `max_dims` is supported for the element width.
+///
+/// Writes as many whole pairs as fit in `out_cap` bytes of `out`, and returns
+/// the number of bytes that every pair needs, so a null `out` with an
+/// `out_cap` of zero can be used to size the buffer.
+size_t lindel_supported_shapes(uint8_t *out, size_t out_cap);
+
+/// Check that a key stored in an integer of `src_bits_declared` bits has no
+/// bits set above `dims * element_bit_width`, which would mean it was encoded
+/// with a different width or number of dimensions, or is corrupt.
+///
+/// Returns 1 if every set bit falls within the values and 0 if it doesn't, or
+/// a negative `LindelStatus` if the width, number of dimensions or
+/// `src_bits_declared` isn't supported.
+int32_t lindel_validate_key(const void *src,
+                            uint8_t src_bits_declared,
+                            size_t dims,
+                            uint8_t element_bit_width);
+
+/// Return the number of leading interleaved bits that two Morton keys of the
+/// same shape have in common, which is the depth of the smallest cell that
+/// contains both of them.  Dividing by `dims` gives the quadtree/octree level.
+///
+/// Returns a negative `LindelStatus` if the shape is invalid.
+int32_t morton_common_prefix_bits(const void *key_a,
+                                  const void *key_b,
+                                  uint8_t element_bit_width,
+                                  size_t dims);
+
+/// Gather every `dims`-th bit of the unaligned u128 at `src` back into the
+/// low `bits` bits of `out`, the inverse of `morton_spread()`.
+///
+/// The bits between them are ignored, so a single dimension of a Morton key
+/// can be extracted by shifting the key right by `dims - 1 - d` first.
+LindelStatus morton_compact(const void *src, uint8_t dims, uint8_t bits, uint64_t *out);
+
+/// Decode a Morton key of `dims` values of `element_bit_width` bits and pack
+/// the values into fixed `field_bits` wide fields of a u128 written to `dest`,
+/// rather than a list.  The first value is stored in the most significant
+/// field and the last in the least significant bits.
+///
+/// Returns `ValueOutOfRange` if a decoded value doesn't fit in `field_bits`.
+LindelStatus morton_decode_packed(uint8_t element_bit_width,
+                                  size_t dims,
+                                  const void *src,
+                                  uint8_t field_bits,
+                                  void *dest);
+
+/// Decode a key produced by `morton_encode_uneven()` with the same `widths`,
+/// writing one u64 per dimension to `dest`.
+LindelStatus morton_decode_uneven(const uint8_t *widths,
+                                  size_t dims,
+                                  const void *src,
+                                  uint64_t *dest);
+
+/// Decode a key produced by `morton_encode_uneven_signed()` with the same
+/// `widths` and `signed_mask`, writing one `LindelHugeint` per dimension to
+/// `dest`, which doesn't need to be aligned.
+LindelStatus morton_decode_uneven_signed(const uint8_t *widths,
+                                         uint32_t signed_mask,
+                                         size_t dims,
+                                         const void *src,
+                                         LindelHugeint *dest);
+
+/// Decode a key produced by `morton_encode_wide()` from `src_len` bytes,
+/// which must be `dims * 8`, writing one u64 per dimension to `dest`.
+LindelStatus morton_decode_wide(const uint8_t *src, size_t src_len, size_t dims, uint64_t *dest);
+
+/// `float_checks` is a combination of `LINDEL_REJECT_NAN` and
+/// `LINDEL_REJECT_INFINITE`, a rejected value returns `NonFiniteValue`.
+/// Any other NaN is encoded as the standard quiet NaN.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_f32_var(const float *ptr,
+                              size_t len,
+                              void *result,
+                              bool big_endian,
+                              uint8_t float_checks);
+
+/// `float_checks` is a combination of `LINDEL_REJECT_NAN` and
+/// `LINDEL_REJECT_INFINITE`, a rejected value returns `NonFiniteValue`.
+/// Any other NaN is encoded as the standard quiet NaN.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_f64_var(const double *ptr,
+                              size_t len,
+                              void *result,
+                              bool big_endian,
+                              uint8_t float_checks);
+
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_u16_var(const uint16_t *ptr, size_t len, void *result, bool big_endian);
+
+/// Returns 16, the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_u16_var_blob(const uint16_t *ptr, size_t len, uint8_t *result);
+
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_u32_var(const uint32_t *ptr, size_t len, void *result, bool big_endian);
 
-void hilbert_encode_u32_var(const uint32_t *ptr, size_t len, void *result);
+/// Returns 16, the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_u32_var_blob(const uint32_t *ptr, size_t len, uint8_t *result);
 
-void hilbert_encode_u64_var(const uint64_t *ptr, size_t len, void *result);
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_u64_var(const uint64_t *ptr, size_t len, void *result, bool big_endian);
 
-void hilbert_encode_u8_var(const uint8_t *ptr, size_t len, void *result);
+/// Returns 16, the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_u64_var_blob(const uint64_t *ptr, size_t len, uint8_t *result);
 
-void morton_encode_u16_var(const uint16_t *ptr, size_t len, void *result);
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_u8_var(const uint8_t *ptr, size_t len, void *result, bool big_endian);
 
-void morton_encode_u32_var(const uint32_t *ptr, size_t len, void *result);
+/// Returns 16, the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_u8_var_blob(const uint8_t *ptr, size_t len, uint8_t *result);
 
-void morton_encode_u64_var(const uint64_t *ptr, size_t len, void *result);
+/// Morton encode `dims` values where each dimension has its own number of
+/// bits given by `widths`, so e.g. X can be finer than Y.
+///
+/// Bits are emitted round-robin starting from the most significant bit of
+/// each value, and a dimension drops out once its bits are exhausted, so with
+/// `widths` of `[20, 12]` the first 24 bits of the key alternate between X and
+/// Y and the last 8 come from the low bits of X.  `values` points to one u64
+/// per dimension and the key is stored in the smallest power of two integer
+/// of at least 8 bits that holds the sum of the widths, at most 128.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_uneven(const uint64_t *values,
+                             const uint8_t *widths,
+                             size_t dims,
+                             void *result);
+
+/// Morton encode `dims` values of per-dimension `widths` like
+/// `morton_encode_uneven()`, where dimension `i` is a two's complement signed
+/// value if bit `i` of `signed_mask` is set and unsigned otherwise.
+///
+/// `values` points to one `LindelHugeint` per dimension, which doesn't need
+/// to be aligned.  Signed values are offset by half their range so negative values
+/// order before positive ones, which only changes the order of the dimensions
+/// that are signed.  A value outside the range of its width and signedness
+/// returns `ValueOutOfRange`.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.
+int32_t morton_encode_uneven_signed(const LindelHugeint *values,
+                                    const uint8_t *widths,
+                                    uint32_t signed_mask,
+                                    size_t dims,
+                                    void *result);
+
+/// Morton encode up to 4 u64 values into a big-endian byte array, for keys
+/// wider than the 128 bits that fit in an integer.
+///
+/// Exactly `dims * 8` bytes are written to `result`, the interleaving is the
+/// same as the other Morton encoders so for one or two dimensions the bytes
+/// are the big-endian bytes of their key.  Because the bytes are big-endian
+/// they sort in the same order as the keys.
+///
+/// Returns the number of bytes written to `result`, or a negative
+/// `LindelStatus` on error.  `BufferTooSmall` is returned if `result_len` is
+/// less than `dims * 8`.
+int32_t morton_encode_wide(const uint64_t *values, size_t dims, uint8_t *result, size_t result_len);
+
+/// Test a batch of Morton keys against a set of key ranges, writing 1 to
+/// `out` for each key that falls within one of the ranges and 0 otherwise.
+///
+/// `ranges` points to `n_ranges` pairs of inclusive `[start, end]` keys which
+/// must be sorted by start and not overlap, and `out` must have room for
+/// `n_keys` bytes.
+LindelStatus morton_in_ranges_batch(const uint64_t *keys,
+                                    size_t n_keys,
+                                    const uint64_t *ranges,
+                                    size_t n_ranges,
+                                    uint8_t *out);
+
+/// Spread the low `bits` bits of `value` apart by `dims - 1` zero bits, the
+/// half of Morton encoding that applies to a single dimension, and write the
+/// result to `out` as an unaligned u128.
+///
+/// Bit `i` of the value lands on bit `i * dims` of the result, which is where
+/// the last dimension's bits go.  Shifting the spread value of dimension `d`
+/// left by `dims - 1 - d` and OR-ing the dimensions together builds the same
+/// key as the Morton encoders, where the first dimension is the most
+/// significant bit of each group.
+///
+/// Returns `ValueOutOfRange` if the value doesn't fit in `bits` bits.
+LindelStatus morton_spread(uint64_t value, uint8_t dims, uint8_t bits, void *out);
+
+LindelStatus perform_decode(uint8_t encoding_type,
+                            uint8_t element_bit_width,
+                            const void *src,
+                            void *dest,
+                            size_t dest_len);
+
+/// Decode a key of `dims` values of `element_bit_width` bits into a buffer
+/// allocated with `duckdb_malloc()`, so the caller doesn't need to size the
+/// destination beforehand.  The number of values is written to `out_count`.
+///
+/// Returns null, with `out_count` set to zero, if the key can't be decoded.
+/// The caller owns the buffer and must release it with `duckdb_free()`.
+void *perform_decode_alloc(uint8_t encoding_type,
+                           uint8_t element_bit_width,
+                           size_t dims,
+                           const void *src,
+                           size_t *out_count);
+
+/// Decode a key produced by `lindel_encode_bit_reversed()` with the same
+/// `reverse_mask`, restoring the order of the bits of the reversed dimensions.
+LindelStatus perform_decode_bit_reversed(uint8_t encoding_type,
+                                         uint8_t element_bit_width,
+                                         uint32_t reverse_mask,
+                                         const void *src,
+                                         void *dest,
+                                         size_t dest_len);
+
+/// Decode an encoded value like `perform_decode()` but verify that the key
+/// could have been produced by an encode of `dest_len` dimensions first.
+///
+/// Only set bits beyond the shape can be detected, so a key of more
+/// dimensions whose extra high bits are zero still decodes.  Use
+/// `lindel_encode_tagged()` and `perform_decode_tagged()` when a decode of
+/// the wrong shape must always be rejected.
+///
+/// Since the decode writes into `dest` while still reading from `src`,
+/// overlapping buffers are also rejected rather than producing corrupt output.
+LindelStatus perform_decode_checked(uint8_t encoding_type,
+                                    uint8_t element_bit_width,
+                                    const void *src,
+                                    void *dest,
+                                    size_t dest_len);
+
+/// Decode a key of `dims` values of `element_bit_width` bits and format the
+/// unsigned values as a NUL terminated string like `[1, 2, 3]`, a one call
+/// sanity check when diagnosing an encode or decode.
+///
+/// Returns null if the key can't be decoded.  The string is allocated with
+/// `duckdb_malloc()`, the caller owns it and must release it with
+/// `duckdb_free()`.
+char *perform_decode_debug(uint8_t encoding_type,
+                           uint8_t element_bit_width,
+                           size_t dims,
+                           const void *src);
+
+/// Decode a key produced by `lindel_encode_dim_order()` with the same
+/// `reverse_dims`, writing the values back in the order they were given.
+LindelStatus perform_decode_dim_order(uint8_t encoding_type,
+                                      uint8_t element_bit_width,
+                                      bool reverse_dims,
+                                      const void *src,
+                                      void *dest,
+                                      size_t dest_len);
+
+/// Decode a key of exactly `dims` values of `element_bit_width` bits.
+///
+/// The number of dimensions is authoritative and the key is read from the
+/// integer an encode of that shape produces, `dims * element_bit_width` bits
+/// rounded up to 8, 16, 32, 64 or 128, so e.g. a 64 bit key of 16 bit
+/// elements is never guessed to be 3 or 4 values.  A key with bits set beyond
+/// what `dims` values occupy is rejected with `KeyOutOfRange`.
+LindelStatus perform_decode_explicit(uint8_t encoding_type,
+                                     uint8_t element_bit_width,
+                                     uint8_t dims,
+                                     const void *src,
+                                     void *dest);
+
+/// Decode a HUGEINT produced by `lindel_encode_hugeint()`, undoing the flip of
+/// its top bit before decoding `dest_len` values of `element_bit_width` bits.
+LindelStatus perform_decode_hugeint(uint8_t encoding_type,
+                                    uint8_t element_bit_width,
+                                    const void *src,
+                                    void *dest,
+                                    size_t dest_len);
+
+/// Decode a key of `dest_len` values of `element_bit_width` bits into lanes
+/// of `dest_bit_width` bits, verifying that no value loses significant bits
+/// when it's narrowed.
+///
+/// Returns `ValueOutOfRange` if a decoded value doesn't fit in
+/// `dest_bit_width` bits, e.g. a value above 255 of a key encoded from u16
+/// values decoded into u8 lanes, in which case `dest` is left unchanged.
+/// `dest` doesn't need to be aligned.
+LindelStatus perform_decode_narrowing(uint8_t encoding_type,
+                                      uint8_t element_bit_width,
+                                      uint8_t dest_bit_width,
+                                      const void *src,
+                                      void *dest,
+                                      size_t dest_len);
+
+/// Decode a key produced by `lindel_encode_offset()` with the same `offsets`,
+/// writing `dest_len` i64 values to `dest`.
+///
+/// Returns `ValueOutOfRange` if adding an offset back leaves the range of an
+/// i64, in which case `dest` isn't written.
+LindelStatus perform_decode_offset(uint8_t encoding_type,
+                                   uint8_t element_bit_width,
+                                   const int64_t *offsets,
+                                   const void *src,
+                                   int64_t *dest,
+                                   size_t dest_len);
+
+/// Decode only the most significant `significant_bits` of a key, treating the
+/// remaining low bits as zero, and clear the bits of each value below that
+/// precision.  This yields the lower corner of the cell the truncated key
+/// represents.
+///
+/// Morton keys may be truncated to any number of bits, but a Hilbert cell is
+/// only axis aligned at whole levels of the curve, so for Hilbert keys
+/// `significant_bits` must be a multiple of `dest_len`.
+LindelStatus perform_decode_prefix(uint8_t encoding_type,
+                                   uint8_t element_bit_width,
+                                   uint8_t significant_bits,
+                                   const void *src,
+                                   void *dest,
+                                   size_t dest_len);
 
-void morton_encode_u8_var(const uint8_t *ptr, size_t len, void *result);
+/// Decode a key produced by `lindel_encode_resolution()` with the same
+/// `resolution_bits`, returning the lower corner of its cell by shifting each
+/// value back left.
+LindelStatus perform_decode_resolution(uint8_t encoding_type,
+                                       uint8_t element_bit_width,
+                                       uint8_t resolution_bits,
+                                       const void *src,
+                                       void *dest,
+                                       size_t dest_len);
 
-void perform_decode(uint8_t encoding_type,
-                    uint8_t element_bit_width,
-                    const void *src,
-                    void *dest,
-                    size_t dest_len);
+/// Decode a key of `dims` values of `element_bit_width` bits and write only
+/// the dimensions whose bit is set in `dim_mask` to `dest`, packed one after
+/// another in dimension order, e.g. a `dim_mask` of `0b0101` writes dimension
+/// 0 then dimension 2.
+///
+/// `dest` must hold `dim_mask.count_ones()` values and doesn't need to be
+/// aligned.  A `dim_mask` that selects a dimension beyond `dims` returns
+/// `InvalidDimensions`.
+LindelStatus perform_decode_subset(uint8_t encoding_type,
+                                   uint8_t element_bit_width,
+                                   size_t dims,
+                                   uint32_t dim_mask,
+                                   const void *src,
+                                   void *dest);
+
+/// Decode a key produced by `lindel_encode_tagged()` from the `src_len`
+/// bytes at `src` into `dest_len` values of `element_bit_width` bits.
+///
+/// Returns `ShapeMismatch` if the key was encoded with a different encoding
+/// type, element width or number of dimensions, or if `src_len` isn't the
+/// size of a tagged key of that shape, in which case `dest` is left
+/// unchanged.
+LindelStatus perform_decode_tagged(uint8_t encoding_type,
+                                   uint8_t element_bit_width,
+                                   const uint8_t *src,
+                                   size_t src_len,
+                                   void *dest,
+                                   size_t dest_len);
+
+/// Decode a key produced by `lindel_encode_transformed()` with the same
+/// matrices, applying their inverses to restore the original values.
+LindelStatus perform_decode_transformed(uint8_t encoding_type,
+                                        uint8_t element_bit_width,
+                                        const uint64_t *matrices,
+                                        const void *src,
+                                        void *dest,
+                                        size_t dest_len);
+
+/// Decode an encoded value like `perform_decode()` for a column of the
+/// `LindelLogicalType` given by `logical_type`, so the SQL layer can decode
+/// straight into the type of the column that was encoded.
+///
+/// The encode functions take the bits of signed and floating point values as
+/// they are, so decoding the bits unchanged already gives exactly the values
+/// that were encoded.  Apart from `centered`, the logical type is therefore
+/// only validated, `InvalidLogicalType` is returned for a type that doesn't
+/// exist at the element width, and every valid type writes the same bits.
+///
+/// With `centered` set, the values are taken to be coordinates of a grid
+/// centered on zero whose sign bits were flipped before encoding, such as the
+/// keys of `lindel_encode_offset()` with zero offsets, and decode to signed
+/// values in `[-2^(w-1), 2^(w-1) - 1]`.  Only the `Signed` logical type can be
+/// centered.
+///
+/// Returns `NullPointer` if `src` or `dest` is null.
+LindelStatus perform_decode_typed(uint8_t encoding_type,
+                                  uint8_t element_bit_width,
+                                  uint8_t logical_type,
+                                  bool centered,
+                                  const void *src,
+                                  void *dest,
+                                  size_t dest_len);
+
+/// Re-encode a key of `dims` values of `element_bit_width` bits from one curve
+/// to another, e.g. to migrate a table keyed by Morton to Hilbert, without
+/// decoding to a list in between.
+///
+/// Returns the number of bytes written to `dest`, or a negative `LindelStatus`
+/// on error.
+int32_t transcode(uint8_t from_encoding,
+                  uint8_t to_encoding,
+                  uint8_t element_bit_width,
+                  size_t dims,
+                  const void *src,
+                  void *dest);
 
 } // extern "C"
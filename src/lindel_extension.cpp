@@ -51,6 +51,64 @@ namespace duckdb
         }
     };
 
+    // Describe a status returned from one of the Rust functions.
+    static string lindelStatusMessage(LindelStatus status)
+    {
+        switch (status)
+        {
+        case LindelStatus::InvalidEncodingType:
+            return "unsupported encoding type";
+        case LindelStatus::InvalidElementBitWidth:
+            return "unsupported element bit width";
+        case LindelStatus::InvalidDimensions:
+            return "unsupported number of dimensions";
+        case LindelStatus::KeyOutOfRange:
+            return "key has bits set beyond the requested number of dimensions";
+        case LindelStatus::OverlappingBuffers:
+            return "source and destination overlap";
+        case LindelStatus::InvalidPrecision:
+            return "unsupported number of significant bits";
+        case LindelStatus::ValueOutOfRange:
+            return "value has bits set beyond the requested precision";
+        case LindelStatus::InvalidLogicalType:
+            return "unsupported logical type for the element bit width";
+        case LindelStatus::NonFiniteValue:
+            return "value is NaN or infinite";
+        case LindelStatus::BufferTooSmall:
+            return "destination is too small for the key";
+        case LindelStatus::SingularMatrix:
+            return "bit transform matrix is not invertible";
+        case LindelStatus::InvalidByteOrder:
+            return "unsupported byte order";
+        case LindelStatus::NullPointer:
+            return "a required pointer is null";
+        case LindelStatus::ShapeMismatch:
+            return "key was encoded with a different shape";
+        default:
+            return StringUtil::Format("failed with status %d", static_cast<int32_t>(status));
+        }
+    }
+
+    // The Rust functions catch any panic and report it as a status rather than
+    // unwinding into DuckDB, so turn any failure into an exception here.
+    static void lindelCheckStatus(LindelStatus status, const char *function_name)
+    {
+        if (status != LindelStatus::Ok)
+        {
+            throw InvalidInputException(StringUtil::Format("%s: %s", function_name, lindelStatusMessage(status)));
+        }
+    }
+
+    // The encoders return the number of bytes they wrote, or a negative status.
+    static int32_t lindelCheckStatus(int32_t result, const char *function_name)
+    {
+        if (result < 0)
+        {
+            lindelCheckStatus(static_cast<LindelStatus>(result), function_name);
+        }
+        return result;
+    }
+
     // This is the "bind" fucntion that is called when we are decoding an array of values.
     //
     // In SQL this will be a function of the form:
@@ -345,7 +403,8 @@ namespace duckdb
             void *output_location = result_data_u8 + result_offset * output_pointer_increment;
             void *source_location = left_data_8 + (left_idx * input_pointer_increment);
 
-            perform_decode(bind_info.encoding_type, output_element_bit_width, source_location, output_location, output_number_of_elements);
+            lindelCheckStatus(perform_decode(bind_info.encoding_type, output_element_bit_width, source_location, output_location, output_number_of_elements),
+                              bind_info.encoding_type == 0 ? "hilbert_decode" : "morton_decode");
         }
 
         if (args.size() == 1)
@@ -530,6 +589,7 @@ namespace duckdb
         // Get a reference to the bind data.
         auto &func_expr = state.expr.Cast<BoundFunctionExpression>();
         auto &bind_info = func_expr.bind_info->Cast<lindelEncodingBindData>();
+        auto function_name = bind_info.encoding_type == 0 ? "hilbert_encode" : "morton_encode";
 
         // This is the size of the array
         auto array_number_of_elements = ArrayType::GetSize(args.data[0].GetType());
@@ -582,12 +642,12 @@ namespace duckdb
                 {
                 case 1:
                 {
-                    encoder((uint64_t *)(left_data_double + left_offset), array_number_of_elements, result_data_u64 + i);
+                    lindelCheckStatus(encoder((uint64_t *)(left_data_double + left_offset), array_number_of_elements, result_data_u64 + i, false), function_name);
                     break;
                 }
                 case 2:
                 {
-                    encoder((uint64_t *)(left_data_double + left_offset), array_number_of_elements, result_data_u128 + i);
+                    lindelCheckStatus(encoder((uint64_t *)(left_data_double + left_offset), array_number_of_elements, result_data_u128 + i, false), function_name);
                     break;
                 }
                 default:
@@ -603,18 +663,18 @@ namespace duckdb
                 {
                 case 1:
                 {
-                    encoder((uint32_t *)(left_data_float + left_offset), array_number_of_elements, result_data_u32 + i);
+                    lindelCheckStatus(encoder((uint32_t *)(left_data_float + left_offset), array_number_of_elements, result_data_u32 + i, false), function_name);
                     break;
                 }
                 case 2:
                 case 3:
                 {
-                    encoder((uint32_t *)(left_data_float + left_offset), array_number_of_elements, result_data_u64 + i);
+                    lindelCheckStatus(encoder((uint32_t *)(left_data_float + left_offset), array_number_of_elements, result_data_u64 + i, false), function_name);
                     break;
                 }
                 case 4:
                 {
-                    hilbert_encode_u32_var((uint32_t *)(left_data_float + left_offset), array_number_of_elements, result_data_u128 + i);
+                    lindelCheckStatus(hilbert_encode_u32_var((uint32_t *)(left_data_float + left_offset), array_number_of_elements, result_data_u128 + i, false), function_name);
                     break;
                 }
                 default:
@@ -630,12 +690,12 @@ namespace duckdb
                 {
                 case 1:
                 {
-                    encoder((uint64_t *)(left_data_64 + left_offset), array_number_of_elements, result_data_u64 + i);
+                    lindelCheckStatus(encoder((uint64_t *)(left_data_64 + left_offset), array_number_of_elements, result_data_u64 + i, false), function_name);
                     break;
                 }
                 case 2:
                 {
-                    encoder((uint64_t *)(left_data_64 + left_offset), array_number_of_elements, result_data_u128 + i);
+                    lindelCheckStatus(encoder((uint64_t *)(left_data_64 + left_offset), array_number_of_elements, result_data_u128 + i, false), function_name);
                     break;
                 }
                 default:
@@ -653,18 +713,18 @@ namespace duckdb
                 {
                 case 1:
                 {
-                    encoder((uint32_t *)(left_data_32 + left_offset), array_number_of_elements, result_data_u32 + i);
+                    lindelCheckStatus(encoder((uint32_t *)(left_data_32 + left_offset), array_number_of_elements, result_data_u32 + i, false), function_name);
                     break;
                 }
                 case 2:
                 case 3:
                 {
-                    encoder((uint32_t *)(left_data_32 + left_offset), array_number_of_elements, result_data_u64 + i);
+                    lindelCheckStatus(encoder((uint32_t *)(left_data_32 + left_offset), array_number_of_elements, result_data_u64 + i, false), function_name);
                     break;
                 }
                 case 4:
                 {
-                    encoder((uint32_t *)(left_data_32 + left_offset), array_number_of_elements, result_data_u128 + i);
+                    lindelCheckStatus(encoder((uint32_t *)(left_data_32 + left_offset), array_number_of_elements, result_data_u128 + i, false), function_name);
                     break;
                 }
                 default:
@@ -681,18 +741,18 @@ namespace duckdb
                 {
                 case 1:
                 {
-                    encoder((uint16_t *)(left_data_16 + left_offset), array_number_of_elements, result_data_u16 + i);
+                    lindelCheckStatus(encoder((uint16_t *)(left_data_16 + left_offset), array_number_of_elements, result_data_u16 + i, false), function_name);
                     break;
                 }
                 case 2:
                 {
-                    encoder((uint16_t *)(left_data_16 + left_offset), array_number_of_elements, result_data_u32 + i);
+                    lindelCheckStatus(encoder((uint16_t *)(left_data_16 + left_offset), array_number_of_elements, result_data_u32 + i, false), function_name);
                     break;
                 }
                 case 3:
                 case 4:
                 {
-                    encoder((uint16_t *)(left_data_16 + left_offset), array_number_of_elements, result_data_u64 + i);
+                    lindelCheckStatus(encoder((uint16_t *)(left_data_16 + left_offset), array_number_of_elements, result_data_u64 + i, false), function_name);
                     break;
                 }
                 case 5:
@@ -700,7 +760,7 @@ namespace duckdb
                 case 7:
                 case 8:
                 {
-                    encoder((uint16_t *)(left_data_16 + left_offset), array_number_of_elements, result_data_u128 + i);
+                    lindelCheckStatus(encoder((uint16_t *)(left_data_16 + left_offset), array_number_of_elements, result_data_u128 + i, false), function_name);
                     break;
                 }
                 default:
@@ -717,18 +777,18 @@ namespace duckdb
                 {
                 case 1:
                 {
-                    encoder((uint8_t *)(left_data_8 + left_offset), array_number_of_elements, result_data_u8 + i);
+                    lindelCheckStatus(encoder((uint8_t *)(left_data_8 + left_offset), array_number_of_elements, result_data_u8 + i, false), function_name);
                     break;
                 }
                 case 2:
                 {
-                    encoder((uint8_t *)(left_data_8 + left_offset), array_number_of_elements, result_data_u16 + i);
+                    lindelCheckStatus(encoder((uint8_t *)(left_data_8 + left_offset), array_number_of_elements, result_data_u16 + i, false), function_name);
                     break;
                 }
                 case 3:
                 case 4:
                 {
-                    encoder((uint8_t *)(left_data_8 + left_offset), array_number_of_elements, result_data_u32 + i);
+                    lindelCheckStatus(encoder((uint8_t *)(left_data_8 + left_offset), array_number_of_elements, result_data_u32 + i, false), function_name);
                     break;
                 }
                 case 5:
@@ -736,7 +796,7 @@ namespace duckdb
                 case 7:
                 case 8:
                 {
-                    encoder((uint8_t *)(left_data_8 + left_offset), array_number_of_elements, result_data_u64 + i);
+                    lindelCheckStatus(encoder((uint8_t *)(left_data_8 + left_offset), array_number_of_elements, result_data_u64 + i, false), function_name);
                     break;
                 }
                 case 9:
@@ -748,7 +808,7 @@ namespace duckdb
                 case 15:
                 case 16:
                 {
-                    encoder((uint8_t *)(left_data_8 + left_offset), array_number_of_elements, result_data_u128 + i);
+                    lindelCheckStatus(encoder((uint8_t *)(left_data_8 + left_offset), array_number_of_elements, result_data_u128 + i, false), function_name);
                     break;
                 }
                 default: